@@ -1,4 +1,105 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// Where the core's options are declared, in `#[options(...)]` on `Ruffle`.
+const OPTIONS_SOURCE: &str = "src/core.rs";
+
 // TODO: Raise an error if libclang isn't installed
 fn main() {
     built::write_built_file().expect("Failed to acquire build-time information");
-}
\ No newline at end of file
+    write_legacy_options();
+}
+
+enum Token {
+    Open,
+    Close,
+    Str(String),
+}
+
+/// The derived `set_core_options` only sends options in the v2 layout, so this copies them into a table
+/// that src/core/legacy_options.rs can send to frontends that only understand v1 or v0 options.
+fn write_legacy_options() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={OPTIONS_SOURCE}");
+
+    let source = fs::read_to_string(OPTIONS_SOURCE).expect("Failed to read the core's options");
+    let start = source.find("#[options(").expect("No #[options(...)] in the core") + "#[options(".len();
+    let end = start + source[start..].find("\n)]").expect("#[options(...)] isn't closed");
+    let mut tokens = tokenize(&source[start..end]).into_iter().peekable();
+
+    let mut table = String::from("pub const LEGACY_OPTIONS: &[LegacyOption] = &[\n");
+    while let Some(token) = tokens.next() {
+        assert!(matches!(token, Token::Open), "Expected an option");
+        // key, categorized description, plain description, info, categorized info, category
+        let fields: Vec<String> = (0..6).map(|_| expect_str(tokens.next())).collect();
+
+        assert!(matches!(tokens.next(), Some(Token::Open)), "Expected {}'s values", fields[0]);
+        let mut values = String::new();
+        while let Some(Token::Open) = tokens.next() {
+            let value = expect_str(tokens.next());
+            let label = match tokens.next() {
+                Some(Token::Str(label)) => {
+                    assert!(matches!(tokens.next(), Some(Token::Close)), "Expected the end of a value");
+                    format!("Some({label:?})")
+                }
+                Some(Token::Close) => "None".to_string(),
+                _ => panic!("Expected a label or the end of a value"),
+            };
+            write!(values, "({value:?}, {label}), ").unwrap();
+        }
+
+        let default = match tokens.next() {
+            Some(Token::Str(default)) => {
+                assert!(matches!(tokens.next(), Some(Token::Close)), "Expected the end of {}", fields[0]);
+                format!("Some({default:?})")
+            }
+            Some(Token::Close) => "None".to_string(),
+            _ => panic!("Expected a default value or the end of {}", fields[0]),
+        };
+
+        writeln!(
+            table,
+            "    LegacyOption {{ key: {:?}, desc: {:?}, info: {:?}, values: &[{values}], default: {default} }},",
+            fields[0], fields[1], fields[3]
+        )
+        .unwrap();
+    }
+    table.push_str("];\n");
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR isn't set");
+    fs::write(Path::new(&out_dir).join("legacy_options.rs"), table).expect("Failed to write legacy_options.rs");
+}
+
+/// Splits the options into braces and (unescaped) string literals; commas and whitespace don't matter.
+fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '"' => {
+                let mut string = String::new();
+                loop {
+                    match chars.next().expect("Unterminated string") {
+                        '"' => break,
+                        '\\' => string.push(chars.next().expect("Unterminated escape")),
+                        c => string.push(c),
+                    }
+                }
+                tokens.push(Token::Str(string));
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+fn expect_str(token: Option<Token>) -> String {
+    match token {
+        Some(Token::Str(string)) => string,
+        _ => panic!("Expected a string"),
+    }
+}
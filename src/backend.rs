@@ -1,6 +1,10 @@
 pub mod log;
 pub mod ui;
 pub mod audio;
+pub mod cache;
+pub mod external;
 pub mod storage;
 pub mod navigator;
-pub mod render;
\ No newline at end of file
+pub mod throttle;
+pub mod render;
+pub mod resample;
\ No newline at end of file
@@ -1,3 +1,5 @@
+use crate::backend::resample::Resampler;
+use crate::options::{AudioChannels, AudioPrecision, ResamplerQuality};
 use ruffle_core::backend::audio::{
     AudioBackend, AudioMixer, DecodeError, RegisterError, SoundHandle, SoundInstanceHandle, SoundTransform,
 };
@@ -8,25 +10,113 @@ use std::time::Duration;
 pub struct RetroAudioBackend {
     mixer: AudioMixer,
     playing: bool,
-    output: [i16; Self::MAX_SAMPLES],
+    /// Whether the frontend has stopped taking audio, e.g. while its menu is open
+    suspended: bool,
+    output: Vec<i16>,
+    /// Intermediate mix buffer, only used for [`AudioPrecision::Float`].
+    float_output: Option<Vec<f32>>,
+    dither_state: u32,
+    /// Sounds that may still be playing; used to skip mixing entirely when there are none
+    active_instances: Vec<SoundInstanceHandle>,
+    /// Whether `output` is already all zeroes
+    output_silent: bool,
+    /// The rate the mixer was created with, which can't change afterwards
+    mix_samplerate: u32,
+    /// The rate reported to the frontend
     output_samplerate: u32,
+    /// Converts from `mix_samplerate` to `output_samplerate` when they differ
+    resampler: Resampler,
+    /// Mixed audio waiting to be resampled, at `mix_samplerate`
+    mix_buffer: Vec<i16>,
+    /// The frontend's frame rate, not the movie's
     fps: f64,
+    /// Fraction of a sample frame owed to the next video frame, since the sample rate is rarely a multiple of the fps
+    frame_remainder: f64,
+    /// How many (interleaved) samples this frame's audio takes up in `output`
+    frame_samples: usize,
+    /// Whether to downmix to mono; the mixer and `output` are stereo either way
+    channels: AudioChannels,
+    /// Whether to send silence while still mixing, so sounds keep advancing
+    muted: bool,
 }
 
 impl RetroAudioBackend {
     pub const MAX_SAMPLES: usize = 8192;
 
-    pub fn new(num_output_channels: u8, output_samplerate: u32) -> Self {
+    /// How many frames' worth of audio the frontend should buffer at minimum.
+    pub const LATENCY_FRAMES: f64 = 2.0;
+
+    /// `output_samplerate` and `fps` are reported to the frontend, and decide how many samples each frame hands over.
+    pub fn new(
+        channels: AudioChannels,
+        mix_samplerate: u32,
+        output_samplerate: u32,
+        fps: f64,
+        precision: AudioPrecision,
+        quality: ResamplerQuality,
+    ) -> Self {
         #[cfg(feature = "profiler")]
         profiling::scope!("RetroAudioBackend::new");
-        let mixer = AudioMixer::new(num_output_channels, output_samplerate);
+        // libretro only takes interleaved stereo, so mono is downmixed after mixing
+        let mixer = AudioMixer::new(2, mix_samplerate);
 
-        Self {
+        let mut backend = Self {
             mixer,
             playing: false,
-            output: [0; Self::MAX_SAMPLES],
+            suspended: false,
+            output: vec![0; Self::MAX_SAMPLES],
+            float_output: match precision {
+                AudioPrecision::Int16 => None,
+                AudioPrecision::Float => Some(vec![0.0; Self::MAX_SAMPLES]),
+            },
+            dither_state: 0x1234_5678,
+            active_instances: Vec::new(),
+            output_silent: true,
+            mix_samplerate,
             output_samplerate,
+            resampler: Resampler::new(mix_samplerate, output_samplerate, quality),
+            mix_buffer: Vec::new(),
             fps: 0.0,
+            frame_remainder: 0.0,
+            frame_samples: 0,
+            channels,
+            muted: false,
+        };
+        backend.set_output_frame_rate(fps);
+        backend
+    }
+
+    /// Paces output for a frontend running at `frame_rate`, and sizes the output buffers to match.
+    pub fn set_output_frame_rate(&mut self, frame_rate: f64) {
+        self.fps = frame_rate;
+        self.frame_remainder = 0.0;
+
+        if self.fps.is_finite() && self.fps >= 1.0 {
+            // Slow movies need more than MAX_SAMPLES per frame, so grow the buffer to fit
+            let num_samples = (self.samples_per_frame().ceil() as usize) * 2;
+            if num_samples > self.output.len() {
+                self.output.resize(num_samples, 0);
+            }
+
+            if let Some(float_output) = &mut self.float_output {
+                if num_samples > float_output.len() {
+                    float_output.resize(num_samples, 0.0);
+                }
+            }
+        }
+    }
+
+    /// Resamples the mixer's output to `output_samplerate` from now on, e.g. after the user changed it mid-session.
+    pub fn set_output_sample_rate(&mut self, output_samplerate: u32) {
+        self.output_samplerate = output_samplerate;
+        self.resampler = Resampler::new(self.mix_samplerate, output_samplerate, self.resampler.quality());
+        self.set_output_frame_rate(self.fps);
+    }
+
+    /// Only matters while the output rate differs from the mixer's; changing it drops whatever input was buffered.
+    pub fn set_resampler_quality(&mut self, quality: ResamplerQuality) {
+        if self.resampler.quality() != quality {
+            self.resampler = Resampler::new(self.mix_samplerate, self.output_samplerate, quality);
         }
     }
 
@@ -34,12 +124,154 @@ impl RetroAudioBackend {
         if !self.fps.is_finite() || self.fps < 1.0 {
             None
         } else {
-            let num_samples = ((self.output_samplerate as usize) / (self.fps as usize)) * 2;
+            Some(&self.output[..self.frame_samples])
+        }
+    }
+
+    pub fn set_channels(&mut self, channels: AudioChannels) {
+        self.channels = channels;
+    }
+
+    /// Unlike [`Self::set_suspended`] or pausing, sounds keep playing (and finishing) while muted;
+    /// they just aren't heard.
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
 
-            Some(&self.output[..num_samples.min(Self::MAX_SAMPLES)])
+    /// Stops mixing until resumed, so sounds pick up where they left off instead of playing on unheard.
+    pub fn set_suspended(&mut self, suspended: bool) {
+        self.suspended = suspended;
+    }
+
+    /// Mixes `elapsed` worth of audio into `output`, to be read with [`Self::current_samples`].
+    /// If `elapsed` is `None`, mixes one frame at the output frame rate.
+    pub fn mix_frame(&mut self, elapsed: Option<Duration>) {
+        #[cfg(feature = "profiler")]
+        profiling::scope!("RetroAudioBackend::mix_frame");
+        if self.fps.is_finite() && self.fps >= 1.0 {
+            // Frames rarely last a whole number of samples (e.g. 29.97 fps at 44100 Hz);
+            // carrying the fraction over keeps the long-run rate exact, so audio doesn't drift from the video
+            let wanted = match elapsed {
+                Some(elapsed) => f64::from(self.output_samplerate) * elapsed.as_secs_f64(),
+                None => self.samples_per_frame(),
+            };
+            let exact = wanted + self.frame_remainder;
+            let frames = exact.floor();
+            self.frame_remainder = exact - frames;
+            self.frame_samples = frames as usize * 2;
+            // Late frames need more room than set_output_frame_rate planned for
+            if self.frame_samples > self.output.len() {
+                self.output.resize(self.frame_samples, 0);
+            }
+
+            // The mixer drops sounds once they finish, after which it no longer knows their position
+            let mixer = &self.mixer;
+            self.active_instances
+                .retain(|&instance| mixer.get_sound_position(instance).is_some());
+            // Ruffle pauses the audio backend along with the timeline, so sounds resume where they left off
+            if !self.playing || self.suspended || self.active_instances.is_empty() {
+                if !self.output_silent {
+                    self.output.fill(0);
+                    self.resampler.reset();
+                    self.output_silent = true;
+                }
+                return;
+            }
+            self.output_silent = false;
+
+            let interval = &mut self.output[..self.frame_samples];
+            if self.resampler.is_passthrough() {
+                Self::mix(&mut self.mixer, self.float_output.as_mut(), &mut self.dither_state, interval);
+            } else {
+                let needed = self.resampler.input_needed(interval.len() / 2);
+                self.mix_buffer.resize(needed * 2, 0);
+                Self::mix(&mut self.mixer, self.float_output.as_mut(), &mut self.dither_state, &mut self.mix_buffer);
+                self.resampler.push(&self.mix_buffer);
+
+                #[cfg(feature = "profiler")]
+                profiling::scope!("Resampler::process");
+                self.resampler.process(interval);
+            }
+
+            if self.muted {
+                interval.fill(0);
+            } else if self.channels == AudioChannels::Mono {
+                Self::downmix(interval);
+            }
         }
+    }
 
-        // samples per frame = samples per second / frames per second
+    /// Mixes enough audio to fill `output`, going through `float_output` if it's there.
+    fn mix(mixer: &mut AudioMixer, float_output: Option<&mut Vec<f32>>, dither_state: &mut u32, output: &mut [i16]) {
+        #[cfg(feature = "profiler")]
+        profiling::scope!("AudioMixer::mix");
+        if let Some(float_output) = float_output {
+            if float_output.len() < output.len() {
+                float_output.resize(output.len(), 0.0);
+            }
+
+            let float_interval = &mut float_output[..output.len()];
+            mixer.mix(float_interval);
+
+            // libretro only accepts 16-bit samples, so quantize as late as possible
+            for (out, sample) in output.iter_mut().zip(float_interval.iter()) {
+                *out = Self::quantize(*sample, dither_state);
+            }
+        } else {
+            mixer.mix(output);
+        }
+    }
+
+    /// The loudest sample in each channel of the mixer's recent history, from 0.0 to 1.0.
+    pub fn peak_levels(&self) -> [f32; 2] {
+        self.mixer
+            .get_sample_history()
+            .iter()
+            .fold([0.0f32; 2], |[left, right], [l, r]| [left.max(l.abs()), right.max(r.abs())])
+    }
+
+    /// Replaces both channels of each interleaved stereo frame with their average.
+    fn downmix(samples: &mut [i16]) {
+        for frame in samples.chunks_exact_mut(2) {
+            let mono = ((i32::from(frame[0]) + i32::from(frame[1])) / 2) as i16;
+            frame[0] = mono;
+            frame[1] = mono;
+        }
+    }
+
+    /// Sample frames per video frame; samples per second / frames per second.
+    fn samples_per_frame(&self) -> f64 {
+        f64::from(self.output_samplerate) / self.fps
+    }
+
+    /// Converts a float sample to 16-bit with triangular dither,
+    /// which trades quantization distortion for a little inaudible noise.
+    fn quantize(sample: f32, dither_state: &mut u32) -> i16 {
+        let mut next = || {
+            // xorshift32; quality doesn't matter much for dither
+            *dither_state ^= *dither_state << 13;
+            *dither_state ^= *dither_state >> 17;
+            *dither_state ^= *dither_state << 5;
+            (*dither_state as f32 / u32::MAX as f32) - 0.5
+        };
+
+        let dither = next() + next();
+        (sample * f32::from(i16::MAX) + dither)
+            .round()
+            .clamp(f32::from(i16::MIN), f32::from(i16::MAX)) as i16
+    }
+
+    /// The smallest audio latency the frontend should use to avoid dropouts,
+    /// or `None` if the frame rate can't be used to compute one.
+    pub fn minimum_latency(output_samplerate: u32, fps: f64) -> Option<Duration> {
+        if !fps.is_finite() || fps < 1.0 || output_samplerate == 0 {
+            return None;
+        }
+
+        let samples_per_frame = f64::from(output_samplerate) / fps;
+        Some(Duration::from_secs_f64(
+            (samples_per_frame * Self::LATENCY_FRAMES) / f64::from(output_samplerate),
+        ))
     }
 }
 
@@ -67,7 +299,9 @@ impl AudioBackend for RetroAudioBackend {
     fn start_sound(&mut self, sound: SoundHandle, settings: &SoundInfo) -> Result<SoundInstanceHandle, DecodeError> {
         #[cfg(feature = "profiler")]
         profiling::scope!("RetroAudioBackend::start_sound");
-        self.mixer.start_sound(sound, settings)
+        let instance = self.mixer.start_sound(sound, settings)?;
+        self.active_instances.push(instance);
+        Ok(instance)
     }
 
     fn start_stream(
@@ -79,19 +313,23 @@ impl AudioBackend for RetroAudioBackend {
     ) -> Result<SoundInstanceHandle, DecodeError> {
         #[cfg(feature = "profiler")]
         profiling::scope!("RetroAudioBackend::start_stream");
-        self.mixer.start_stream(stream_handle, clip_frame, clip_data, handle)
+        let instance = self.mixer.start_stream(stream_handle, clip_frame, clip_data, handle)?;
+        self.active_instances.push(instance);
+        Ok(instance)
     }
 
     fn stop_sound(&mut self, sound: SoundInstanceHandle) {
         #[cfg(feature = "profiler")]
         profiling::scope!("RetroAudioBackend::stop_sound");
-        self.mixer.stop_sound(sound)
+        self.mixer.stop_sound(sound);
+        self.active_instances.retain(|&instance| instance != sound);
     }
 
     fn stop_all_sounds(&mut self) {
         #[cfg(feature = "profiler")]
         profiling::scope!("RetroAudioBackend::stop_all_sounds");
-        self.mixer.stop_all_sounds()
+        self.mixer.stop_all_sounds();
+        self.active_instances.clear();
     }
 
     fn get_sound_position(&self, instance: SoundInstanceHandle) -> Option<f64> {
@@ -135,24 +373,17 @@ impl AudioBackend for RetroAudioBackend {
     }
 
     fn tick(&mut self) {
-        #[cfg(feature = "profiler")]
-        profiling::scope!("RetroAudioBackend::tick");
-        if self.fps.is_finite() && self.fps > 1.0 {
-            let num_samples = ((self.output_samplerate as usize) / (self.fps as usize)) * 2;
-            let interval = &mut self.output[..num_samples.min(Self::MAX_SAMPLES)];
-
-            #[cfg(feature = "profiler")]
-            profiling::scope!("AudioMixer::mix");
-            self.mixer.mix(interval);
-        }
+        // Mixing happens once per retro_run in `mix_frame` instead, so a movie that runs several frames per
+        // retro_run, or changes its frame rate, still hands the frontend exactly as many samples as it expects
     }
 
-    fn set_frame_rate(&mut self, frame_rate: f64) {
-        self.fps = frame_rate;
+    fn set_frame_rate(&mut self, _frame_rate: f64) {
+        // Samples are paced by the frontend's frame rate instead, which the core passes to set_output_frame_rate
+        // once the frontend has agreed to it
     }
 
     fn position_resolution(&self) -> Option<Duration> {
-        Some(Duration::from_secs_f64(f64::from(Self::MAX_SAMPLES as u32) / f64::from(self.output_samplerate)))
+        Some(Duration::from_secs_f64(f64::from(Self::MAX_SAMPLES as u32) / f64::from(self.mix_samplerate)))
     }
 
     fn volume(&self) -> f32 {
@@ -167,3 +398,97 @@ impl AudioBackend for RetroAudioBackend {
         self.mixer.get_sample_history()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ruffle_core::swf::{AudioCompression, SoundEvent};
+
+    const SAMPLE_RATE: u32 = 44100;
+    const NTSC_FPS: f64 = 30000.0 / 1001.0;
+
+    fn backend(channels: AudioChannels) -> RetroAudioBackend {
+        RetroAudioBackend::new(
+            channels,
+            SAMPLE_RATE,
+            SAMPLE_RATE,
+            NTSC_FPS,
+            AudioPrecision::Int16,
+            ResamplerQuality::Linear,
+        )
+    }
+
+    /// Starts a looping tone that's louder in the left channel than the right, so the channels can be told apart.
+    fn play_tone(backend: &mut RetroAudioBackend) {
+        const FRAMES: usize = 4410;
+        let mut data = Vec::with_capacity(FRAMES * 4);
+        for i in 0..FRAMES {
+            let sign = if (i / 50) % 2 == 0 { 1 } else { -1 };
+            data.extend_from_slice(&(sign * 8000i16).to_le_bytes());
+            data.extend_from_slice(&(sign * 2000i16).to_le_bytes());
+        }
+
+        let sound = Sound {
+            id: 1,
+            format: SoundFormat {
+                compression: AudioCompression::Uncompressed,
+                sample_rate: SAMPLE_RATE as u16,
+                is_stereo: true,
+                is_16_bit: true,
+            },
+            num_samples: FRAMES as u32,
+            data: &data,
+        };
+        let handle = backend.register_sound(&sound).unwrap();
+        let info = SoundInfo {
+            event: SoundEvent::Event,
+            in_sample: None,
+            out_sample: None,
+            num_loops: u16::MAX,
+            envelope: None,
+        };
+        backend.start_sound(handle, &info).unwrap();
+        backend.play();
+    }
+
+    #[test]
+    fn mix_frame_keeps_pace_with_the_frame_rate() {
+        let mut backend = backend(AudioChannels::Stereo);
+        let frames = 600;
+        let mut total = 0;
+        for _ in 0..frames {
+            backend.mix_frame(None);
+            total += backend.current_samples().unwrap().len() / 2;
+        }
+
+        let expected = f64::from(SAMPLE_RATE) * (f64::from(frames) / NTSC_FPS);
+        assert!((total as f64 - expected).abs() <= 1.0, "{total} samples, expected {expected}");
+    }
+
+    #[test]
+    fn output_is_interleaved_stereo_for_every_channel_count() {
+        let frames = (f64::from(SAMPLE_RATE) / NTSC_FPS).floor() as usize;
+        for channels in [AudioChannels::Stereo, AudioChannels::Mono] {
+            let mut backend = backend(channels);
+            play_tone(&mut backend);
+            backend.mix_frame(None);
+
+            // libretro always takes two samples per frame, even when they're the same
+            let samples = backend.current_samples().unwrap();
+            assert_eq!(samples.len(), frames * 2, "{channels:?}");
+            let differs = samples.chunks_exact(2).any(|frame| frame[0] != frame[1]);
+            assert_eq!(differs, channels == AudioChannels::Stereo, "{channels:?}");
+        }
+    }
+
+    #[test]
+    fn current_samples_has_audio_while_a_sound_plays() {
+        let mut backend = backend(AudioChannels::Stereo);
+        backend.mix_frame(None);
+        assert!(backend.current_samples().unwrap().iter().all(|&sample| sample == 0));
+
+        play_tone(&mut backend);
+        backend.mix_frame(None);
+        assert!(backend.current_samples().unwrap().iter().any(|&sample| sample != 0));
+    }
+}
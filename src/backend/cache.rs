@@ -0,0 +1,117 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::{debug, warn};
+use ruffle_core::backend::storage::StorageBackend;
+
+use crate::backend::storage::RetroVfsStorageBackend;
+
+/// How long a response stays fresh if the server doesn't say otherwise.
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// On-disk cache of HTTP responses, keyed by URL.
+/// Each entry is stored as `<expiry in seconds since the epoch>\n<url>\n<body>`.
+pub struct HttpCache {
+    storage: RetroVfsStorageBackend,
+}
+
+impl HttpCache {
+    pub fn new(storage: RetroVfsStorageBackend) -> Self {
+        Self { storage }
+    }
+
+    /// Returns the cached body for `url`, if there is one and it hasn't expired.
+    pub fn get(&self, url: &str) -> Option<Vec<u8>> {
+        let entry = self.storage.get(&Self::key(url))?;
+        let (expiry, rest) = Self::split_line(&entry)?;
+        let (cached_url, body) = Self::split_line(rest)?;
+
+        if cached_url != url.as_bytes() {
+            // Hash collision, this entry belongs to another URL
+            return None;
+        }
+
+        let expiry: u64 = std::str::from_utf8(expiry).ok()?.parse().ok()?;
+        if Self::now() >= expiry {
+            debug!("Cached response for {url} has expired");
+            return None;
+        }
+
+        debug!("Using cached response for {url}");
+        Some(body.to_vec())
+    }
+
+    /// Caches `body` for `url`, honoring the response's `Cache-Control` header if it has one.
+    pub fn put(&mut self, url: &str, body: &[u8], cache_control: Option<&str>) {
+        let max_age = match Self::max_age(cache_control) {
+            Some(max_age) => max_age,
+            None => return,
+        };
+
+        let expiry = Self::now() + max_age.as_secs();
+        let mut entry = format!("{expiry}\n{url}\n").into_bytes();
+        entry.extend_from_slice(body);
+
+        if !self.storage.put(&Self::key(url), &entry) {
+            warn!("Failed to cache response for {url}");
+        }
+    }
+
+    /// Returns `None` if the response must not be cached.
+    fn max_age(cache_control: Option<&str>) -> Option<Duration> {
+        let cache_control = match cache_control {
+            Some(cache_control) => cache_control,
+            None => return Some(DEFAULT_MAX_AGE),
+        };
+
+        let mut max_age = DEFAULT_MAX_AGE;
+        for directive in cache_control.split(',').map(str::trim) {
+            match directive.split_once('=') {
+                _ if directive.eq_ignore_ascii_case("no-store") || directive.eq_ignore_ascii_case("no-cache") => {
+                    return None
+                }
+                Some((name, value)) if name.trim().eq_ignore_ascii_case("max-age") => {
+                    max_age = Duration::from_secs(value.trim().parse().ok()?);
+                }
+                _ => {}
+            }
+        }
+
+        Some(max_age)
+    }
+
+    /// FNV-1a of the URL, since the key has to stay the same across builds for entries to be found again;
+    /// `DefaultHasher`'s algorithm isn't guaranteed to.
+    fn key(url: &str) -> String {
+        let hash = url.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        });
+        format!("{hash:016x}")
+    }
+
+    fn split_line(data: &[u8]) -> Option<(&[u8], &[u8])> {
+        let newline = data.iter().position(|&b| b == b'\n')?;
+        Some((&data[..newline], &data[newline + 1..]))
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_is_fnv1a_of_the_url() {
+        assert_eq!(HttpCache::key(""), "cbf29ce484222325");
+        assert_eq!(HttpCache::key("a"), "af63dc4c8601ec8c");
+        assert_eq!(HttpCache::key("https://example.com/movie.swf"), "775f94fc32fc8256");
+    }
+}
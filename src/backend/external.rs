@@ -0,0 +1,104 @@
+use std::cell::Cell;
+use std::sync::Arc;
+
+use log::{info, warn};
+use ruffle_core::context::UpdateContext;
+use ruffle_core::external::{ExternalInterfaceMethod, ExternalInterfaceProvider, Value as ExternalValue};
+use rust_libretro::environment;
+use rust_libretro::sys::retro_environment_t;
+
+use crate::options::ExternalInterfaceMode;
+
+/// ExternalInterface call names that close the core when actions are enabled.
+const EXIT_METHODS: &[&str] = &["exit", "quit", "close"];
+
+/// Stands in for the JavaScript host that ExternalInterface calls expect.
+/// There's nothing to call into, so calls are logged and (optionally) mapped to core actions.
+/// Also receives `fscommand`s, since Ruffle routes them through the same providers.
+pub struct RetroExternalInterfaceProvider {
+    mode: ExternalInterfaceMode,
+    allow_quit: bool,
+    environment: Arc<Cell<retro_environment_t>>,
+}
+
+impl RetroExternalInterfaceProvider {
+    pub fn new(mode: ExternalInterfaceMode, allow_quit: bool, environment: Arc<Cell<retro_environment_t>>) -> Self {
+        Self {
+            mode,
+            allow_quit,
+            environment,
+        }
+    }
+}
+
+impl ExternalInterfaceProvider for RetroExternalInterfaceProvider {
+    fn get_method(&self, name: &str) -> Option<Box<dyn ExternalInterfaceMethod>> {
+        if self.mode == ExternalInterfaceMode::Disabled {
+            // We're only here for fscommand("quit")
+            return None;
+        }
+
+        let action = match self.mode {
+            ExternalInterfaceMode::Actions if EXIT_METHODS.contains(&name) => ExternalAction::Exit,
+            _ => ExternalAction::None,
+        };
+
+        Some(Box::new(RetroExternalInterfaceMethod {
+            name: name.to_string(),
+            action,
+            environment: self.environment.clone(),
+        }))
+    }
+
+    fn on_callback_available(&self, name: &str) {
+        info!("Content registered ExternalInterface callback {name:?}, but nothing will call it");
+    }
+
+    fn on_fs_command(&self, command: &str, args: &str) -> bool {
+        if !command.eq_ignore_ascii_case("quit") {
+            return false;
+        }
+
+        if !self.allow_quit {
+            info!("Content called fscommand({command:?}, {args:?}), but quitting isn't allowed");
+            return true;
+        }
+
+        info!("Content called fscommand({command:?}, {args:?}), closing the core");
+        if !unsafe { environment::shutdown(self.environment.get()) } {
+            warn!("RETRO_ENVIRONMENT_SHUTDOWN failed");
+        }
+
+        true
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ExternalAction {
+    None,
+    Exit,
+}
+
+struct RetroExternalInterfaceMethod {
+    name: String,
+    action: ExternalAction,
+    environment: Arc<Cell<retro_environment_t>>,
+}
+
+impl ExternalInterfaceMethod for RetroExternalInterfaceMethod {
+    fn call(&self, _context: &mut UpdateContext<'_, '_>, args: &[ExternalValue]) -> ExternalValue {
+        info!("ExternalInterface.call({:?}, {args:?})", self.name);
+
+        match self.action {
+            ExternalAction::Exit => {
+                info!("Content called ExternalInterface method {:?}, closing the core", self.name);
+                if !unsafe { environment::shutdown(self.environment.get()) } {
+                    warn!("RETRO_ENVIRONMENT_SHUTDOWN failed");
+                }
+            }
+            ExternalAction::None => {}
+        }
+
+        ExternalValue::Null
+    }
+}
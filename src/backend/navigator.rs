@@ -1,5 +1,6 @@
 //! Navigator backend for web
 
+use futures::task::noop_waker;
 use isahc::{
     config::RedirectPolicy, prelude::*, AsyncReadResponseExt, HttpClient, Request as IsahcRequest,
 };
@@ -8,45 +9,132 @@ use ruffle_core::backend::navigator::{
 };
 use ruffle_core::indexmap::IndexMap;
 use ruffle_core::loader::Error;
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::path::PathBuf;
+use std::panic::{self, AssertUnwindSafe};
 use std::rc::Rc;
-use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::task::{Context, Poll};
 //use rust_libretro::contexts::GenericContext;
-use rust_libretro_sys::retro_game_info;
+use rust_libretro_sys::{retro_environment_t, retro_log_level, retro_vfs_interface};
 use url::Url;
+use crate::backend::cache::HttpCache;
+use crate::backend::storage;
+use crate::backend::throttle::RequestThrottle;
 use crate::core::config::Config;
+use crate::options::{FileAccessPolicy, WebBrowserAccess};
+use crate::util;
+
+/// How long to show the notice for a link that wasn't opened, in milliseconds.
+const NAVIGATION_NOTICE_DURATION: u32 = 3000;
+
+/// How long to show the notice for a local file the movie read, in milliseconds.
+const FILE_ACCESS_NOTICE_DURATION: u32 = 3000;
+
+/// Runs the tasks the navigator is given through `spawn_future`, such as loading other movies.
+/// The player owns the navigator, but has to be unlocked while these run (loaders lock it themselves),
+/// so the core keeps its own handle to the same queue.
+#[derive(Clone, Default)]
+pub struct RetroExecutor {
+    /// Tasks that haven't finished yet.
+    tasks: Rc<RefCell<Vec<OwnedFuture<(), Error>>>>,
+}
+
+impl RetroExecutor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn spawn(&self, future: OwnedFuture<(), Error>) {
+        self.tasks.borrow_mut().push(future);
+    }
+
+    /// Polls every task once, dropping the ones that finished.
+    /// Tasks aren't woken up in between, so this should be called once per frame.
+    pub fn tick(&self) {
+        // Taken out of the queue, since a task can spawn more tasks while it's polled
+        let mut tasks = std::mem::take(&mut *self.tasks.borrow_mut());
+        let waker = noop_waker();
+        let mut context = Context::from_waker(&waker);
+        tasks.retain_mut(|future| {
+            // Unwinding into the frontend would abort it, so a panicking task only takes itself down
+            match panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(&mut context))) {
+                Ok(Poll::Pending) => true,
+                Ok(Poll::Ready(Ok(()))) => false,
+                Ok(Poll::Ready(Err(e))) => {
+                    log::error!("Asynchronous error occurred: {e}");
+                    false
+                }
+                Err(_) => {
+                    log::error!("A navigator task panicked and was dropped");
+                    false
+                }
+            }
+        });
+
+        let mut queue = self.tasks.borrow_mut();
+        tasks.append(&mut queue);
+        *queue = tasks;
+    }
+}
 
 /// Implementation of `NavigatorBackend` for non-web environments that can call
 /// out to a web browser.
-pub struct RetroNavigatorBackend<'a> {
-    /// Sink for tasks sent to us through `spawn_future`.
-    channel: Sender<OwnedFuture<(), Error>>,
+pub struct RetroNavigatorBackend {
+    /// Where tasks sent to us through `spawn_future` are run.
+    executor: RetroExecutor,
     /// Event sink to trigger a new task poll.
     //event_loop: EventLoopProxy<RuffleEvent>,
 
     /// The url to use for all relative fetches.
     base_url: Url,
 
+    /// The folder a local movie was loaded from; files in it (e.g. the rest of a split movie)
+    /// are part of the content, so they're read whatever the file access policy is
+    content_directory: Option<PathBuf>,
+
     // Client to use for network requests
     client: Option<Rc<HttpClient>>,
 
-    config: &'a Config,
+    // Responses to GET requests, if caching is enabled
+    cache: Option<Rc<RefCell<HttpCache>>>,
+
+    // Keeps bursts of requests from opening too many connections at once
+    throttle: Rc<RequestThrottle>,
+
+    // For telling the user about links that weren't opened
+    environment: Arc<Cell<retro_environment_t>>,
+
+    // Local files are read through the frontend's VFS like SharedObjects are, if it has one
+    vfs: Arc<Cell<Option<retro_vfs_interface>>>,
+
+    /// The settings as of when the movie was loaded
+    config: Config,
 }
 
-impl<'a> RetroNavigatorBackend<'a> {
+impl RetroNavigatorBackend {
     /// Construct a navigator backend with fetch and async capability.
     pub fn new(
-        game_info: &retro_game_info,
         movie_url: Url,
-        channel: Sender<OwnedFuture<(), Error>>,
+        executor: RetroExecutor,
         //event_loop: EventLoopProxy<RuffleEvent>,
         proxy: Option<Url>,
-        config: &'a Config,
+        cache: Option<HttpCache>,
+        environment: Arc<Cell<retro_environment_t>>,
+        vfs: Arc<Cell<Option<retro_vfs_interface>>>,
+        config: Config,
     ) -> Self {
         let proxy = proxy.and_then(|url| url.as_str().parse().ok());
-        let builder = HttpClient::builder()
+        let mut builder = HttpClient::builder()
             .proxy(proxy)
             .redirect_policy(RedirectPolicy::Follow);
 
+        // Includes User-Agent, if the user wants to mimic the movie's original host
+        for (name, value) in &config.http_headers {
+            builder = builder.default_header(name.as_str(), value.as_str());
+        }
+
         let client = builder.build().ok().map(Rc::new);
         let mut base_url = movie_url;
 
@@ -59,16 +147,36 @@ impl<'a> RetroNavigatorBackend<'a> {
             .pop()
             .push("");
 
+        let content_directory = match base_url.scheme() {
+            "file" => base_url.to_file_path().ok(),
+            _ => None,
+        };
+
         Self {
-            channel,
+            executor,
             client,
             base_url,
+            content_directory,
+            cache: cache.filter(|_| config.http_cache).map(|cache| Rc::new(RefCell::new(cache))),
+            throttle: RequestThrottle::new(config.max_requests),
+            environment,
+            vfs,
             config,
         }
     }
+
+    fn notify(&self, message: &str, duration: u32) {
+        let result = unsafe {
+            util::message::set_message(self.environment.get(), message, duration, 0, retro_log_level::RETRO_LOG_INFO)
+        };
+        if let Err(e) = result {
+            log::info!("{message}");
+            log::warn!("Failed to show message: {e}");
+        }
+    }
 }
 
-impl<'a> NavigatorBackend for RetroNavigatorBackend<'a> {
+impl NavigatorBackend for RetroNavigatorBackend {
     fn navigate_to_url(
         &self,
         url: &str,
@@ -92,7 +200,12 @@ impl<'a> NavigatorBackend for RetroNavigatorBackend<'a> {
         };
 
         let modified_url = match vars_method {
-            Some((_, query_pairs)) => {
+            Some((method, query_pairs)) => {
+                // A browser can't be made to POST, so the variables go in the query either way
+                if matches!(method, NavigationMethod::Post) {
+                    log::debug!("Sending POST variables for {parsed_url} in its query string");
+                }
+
                 {
                     //lifetime limiter because we don't have NLL yet
                     let mut modifier = parsed_url.query_pairs_mut();
@@ -109,10 +222,19 @@ impl<'a> NavigatorBackend for RetroNavigatorBackend<'a> {
 
         let processed_url = self.pre_process_url(modified_url);
 
-        match webbrowser::open(processed_url.as_ref()) {
-            Ok(_output) => {}
-            Err(e) => log::error!("Could not open URL {}: {}", processed_url.as_str(), e),
-        };
+        match self.config.web_browser_access {
+            WebBrowserAccess::Ignore => {
+                log::info!("Web browser access is disabled, not opening {processed_url}");
+            }
+            WebBrowserAccess::Notify => {
+                log::info!("Web browser access is disabled, not opening {processed_url}");
+                self.notify(&format!("The movie tried to open {processed_url}"), NAVIGATION_NOTICE_DURATION);
+            }
+            WebBrowserAccess::OpenInBrowser => match webbrowser::open(processed_url.as_ref()) {
+                Ok(_output) => {}
+                Err(e) => log::error!("Could not open URL {}: {}", processed_url.as_str(), e),
+            },
+        }
     }
 
     fn fetch(&self, request: Request) -> OwnedFuture<Response, Error> {
@@ -128,45 +250,72 @@ impl<'a> NavigatorBackend for RetroNavigatorBackend<'a> {
         let processed_url = self.pre_process_url(full_url);
 
         let client = self.client.clone();
+        let cache = self.cache.clone();
+        let throttle = self.throttle.clone();
 
         match processed_url.scheme() {
-            "file" => Box::pin(async move {
-                let path = processed_url.to_file_path().unwrap_or_default();
-
-                let url = processed_url.into();
-
-                let body = std::fs::read(&path).or_else(|e| {
-                    /*
-                    if cfg!(feature = "sandbox") {
-                        use rfd::{FileDialog, MessageButtons, MessageDialog, MessageLevel};
-                        use std::io::ErrorKind;
-
-                        // TODO: Don't show a file dialog, but let the user configure what the policy should be
-                        if e.kind() == ErrorKind::PermissionDenied {
-                            let attempt_sandbox_open = MessageDialog::new()
-                                .set_level(MessageLevel::Warning)
-                                .set_description(&format!("The current movie is attempting to read files stored in {}.\n\nTo allow it to do so, click Yes, and then Open to grant read access to that directory.\n\nOtherwise, click No to deny access.", path.parent().unwrap().to_string_lossy()))
-                                .set_buttons(MessageButtons::YesNo)
-                                .show();
-
-                            if attempt_sandbox_open {
-                                FileDialog::new().set_directory(&path).pick_folder();
-
-                                return std::fs::read(&path);
-                            }
-                        }
+            "file" => {
+                let path = match processed_url.to_file_path() {
+                    Ok(path) => path,
+                    Err(_) => {
+                        let msg = format!("{processed_url} isn't a local path");
+                        return Box::pin(async move { Err(Error::FetchError(msg)) });
                     }
-                    */
+                };
 
-                    Err(e)
-                }).map_err(|e| Error::FetchError(e.to_string()))?;
+                // URLs are normalized when joined, so a path can't climb out of the folder with ".."
+                let in_content = self
+                    .content_directory
+                    .as_ref()
+                    .map_or(false, |directory| path.starts_with(directory));
+                match self.config.file_access_policy {
+                    _ if in_content => {}
+                    FileAccessPolicy::Never => {
+                        let msg = format!("Local file access is disabled, not reading {processed_url}");
+                        return Box::pin(async move { Err(Error::FetchError(msg)) });
+                    }
+                    FileAccessPolicy::Notify => {
+                        self.notify(&format!("The movie is reading {processed_url}"), FILE_ACCESS_NOTICE_DURATION);
+                    }
+                    FileAccessPolicy::Always => {}
+                }
 
-                Ok(Response { url, body })
-            }),
+                let vfs = self.vfs.get();
+                Box::pin(async move {
+                    let url = processed_url.into();
+
+                    let body = match vfs {
+                        Some(vfs) => storage::read_file(&vfs, &path)
+                            .ok_or_else(|| Error::FetchError(format!("Failed to read {path:?}")))?,
+                        None => std::fs::read(&path).map_err(|e| Error::FetchError(e.to_string()))?,
+                    };
+
+                    Ok(Response { url, body })
+                })
+            }
+            _ if !self.config.network_access => {
+                let msg = format!("Network access is disabled, not fetching {processed_url}");
+                Box::pin(async move { Err(Error::FetchError(msg)) })
+            }
+            _ if self.config.local_only => {
+                let msg = format!("Movie is local-only (per its FileAttributes), not fetching {processed_url}");
+                Box::pin(async move { Err(Error::FetchError(msg)) })
+            }
             _ => Box::pin(async move {
+                let cache = cache.filter(|_| matches!(request.method(), NavigationMethod::Get));
+                if let Some(body) = cache.as_ref().and_then(|cache| cache.borrow().get(processed_url.as_str())) {
+                    return Ok(Response {
+                        url: processed_url.into(),
+                        body,
+                    });
+                }
+
                 let client =
                     client.ok_or_else(|| Error::FetchError("Network unavailable".to_string()))?;
 
+                // Held until the whole body is read
+                let _permit = throttle.acquire().await;
+
                 let isahc_request = match request.method() {
                     NavigationMethod::Get => IsahcRequest::get(processed_url.to_string()),
                     NavigationMethod::Post => IsahcRequest::post(processed_url.to_string()),
@@ -189,6 +338,12 @@ impl<'a> NavigatorBackend for RetroNavigatorBackend<'a> {
                     )));
                 }
 
+                let cache_control = response
+                    .headers()
+                    .get("cache-control")
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+
                 let url = if let Some(uri) = response.effective_uri() {
                     uri.to_string()
                 } else {
@@ -201,25 +356,126 @@ impl<'a> NavigatorBackend for RetroNavigatorBackend<'a> {
                     .await
                     .map_err(|e| Error::FetchError(e.to_string()))?;
 
+                if let Some(cache) = &cache {
+                    cache.borrow_mut().put(processed_url.as_str(), &body, cache_control.as_deref());
+                }
+
                 Ok(Response { url, body })
             }),
         }
     }
 
     fn spawn_future(&mut self, future: OwnedFuture<(), Error>) {
-        self.channel.send(future).expect("working channel send");
-
-        // if self.event_loop.send_event(RuffleEvent::TaskPoll).is_err() {
-        //     log::warn!(
-        //         "A task was queued on an event loop that has already ended. It will not be polled."
-        //     );
-        // }
+        // Polled by `RetroExecutor::tick`, since libretro cores have no event loop of their own to wake
+        self.executor.spawn(future);
     }
 
     fn pre_process_url(&self, mut url: Url) -> Url {
-        if self.config.upgrade_to_https && url.scheme() == "http" && url.set_scheme("https").is_err() {
-            log::error!("Url::set_scheme failed on: {}", url);
+        if self.config.upgrade_to_https && url.scheme() == "http" {
+            if url.set_scheme("https").is_err() {
+                log::error!("Url::set_scheme failed on: {}", url);
+            } else {
+                log::debug!("Upgraded {url} to HTTPS");
+            }
         }
         url
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use ruffle_core::backend::log::LogBackend;
+    use ruffle_core::tag_utils::SwfMovie;
+    use ruffle_core::PlayerBuilder;
+    use std::path::Path;
+
+    /// Keeps what movies trace, to check which ones ran.
+    struct TraceLog(Rc<RefCell<Vec<String>>>);
+
+    impl LogBackend for TraceLog {
+        fn avm_trace(&self, message: &str) {
+            self.0.borrow_mut().push(message.to_string());
+        }
+    }
+
+    fn navigator(file_access_policy: FileAccessPolicy) -> RetroNavigatorBackend {
+        let mut config = Config::new();
+        config.file_access_policy = file_access_policy;
+        RetroNavigatorBackend::new(
+            local_file("content/movie.swf", b"FWS"),
+            RetroExecutor::new(),
+            None,
+            None,
+            Arc::new(Cell::new(None)),
+            Arc::new(Cell::new(None)),
+            config,
+        )
+    }
+
+    /// Writes `contents` to a file that no other test, or concurrent run of this one, will write to.
+    fn local_file(name: &str, contents: &[u8]) -> Url {
+        let directory = std::env::temp_dir().join(format!("ruffle_libretro_{}", std::process::id()));
+        let path = directory.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, contents).unwrap();
+        Url::from_file_path(path).unwrap()
+    }
+
+    #[test]
+    fn never_rejects_local_files() {
+        let url = local_file("never_rejects_local_files.swf", b"FWS");
+        let result = block_on(navigator(FileAccessPolicy::Never).fetch(Request::get(url.to_string())));
+        assert!(matches!(result, Err(Error::FetchError(_))));
+    }
+
+    #[test]
+    fn always_reads_local_files() {
+        let url = local_file("always_reads_local_files.swf", b"FWS");
+        let response = block_on(navigator(FileAccessPolicy::Always).fetch(Request::get(url.to_string()))).unwrap();
+        assert_eq!(response.body, b"FWS");
+    }
+
+    #[test]
+    fn never_still_reads_files_beside_the_content() {
+        let url = local_file("content/child.swf", b"FWS");
+        let response = block_on(navigator(FileAccessPolicy::Never).fetch(Request::get(url.to_string()))).unwrap();
+        assert_eq!(response.body, b"FWS");
+    }
+
+    #[test]
+    fn loads_child_movie_into_level1() {
+        // parent.swf calls loadMovieNum("child.swf", 1), and child.swf traces "child loaded"
+        let parent = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/swfs/load_movie_num/parent.swf");
+        let movie_url = Url::from_file_path(&parent).unwrap();
+        let movie = SwfMovie::from_data(&std::fs::read(&parent).unwrap(), movie_url.to_string(), None).unwrap();
+
+        // With the default settings, like a split movie would be played
+        let executor = RetroExecutor::new();
+        let navigator = RetroNavigatorBackend::new(
+            movie_url,
+            executor.clone(),
+            None,
+            None,
+            Arc::new(Cell::new(None)),
+            Arc::new(Cell::new(None)),
+            Config::new(),
+        );
+        let traces = Rc::new(RefCell::new(Vec::new()));
+        let player = PlayerBuilder::new()
+            .with_navigator(navigator)
+            .with_log(TraceLog(traces.clone()))
+            .with_movie(movie)
+            .with_autoplay(true)
+            .build();
+
+        // Like on_run: the loader locks the player itself, so it's ticked while the player is unlocked
+        for _ in 0..10 {
+            player.lock().unwrap().run_frame();
+            executor.tick();
+        }
+
+        assert_eq!(*traces.borrow(), ["child loaded"]);
+    }
+}
@@ -1,5 +1,6 @@
 use std::ffi::CStr;
 
+use log::warn;
 use rust_libretro::contexts::LoadGameContext;
 use rust_libretro::{anyhow, environment, retro_hw_context_destroyed_callback, retro_hw_context_reset_callback};
 use rust_libretro_sys::retro_hw_context_type::*;
@@ -9,7 +10,11 @@ use rust_libretro_sys::{
 };
 use thiserror::Error as ThisError;
 
+pub mod budget;
+pub mod diagnostics;
 pub mod opengl;
+pub mod post_process;
+pub mod software;
 pub mod vulkan;
 mod wgpu;
 
@@ -134,16 +139,16 @@ pub fn enable_hw_render(ctx: &mut LoadGameContext, preferred_renderer: retro_hw_
     Ok(())
 }
 
+/// Enables the Vulkan context negotiation interface if using Vulkan, otherwise does nothing.
+/// Failure isn't fatal; the frontend will then pick the device itself.
 pub fn enable_hw_render_negotiation_interface(
     ctx: &mut LoadGameContext,
     preferred_renderer: retro_hw_context_type,
-) -> anyhow::Result<()> {
+    validation: bool,
+) {
     if preferred_renderer == RETRO_HW_CONTEXT_VULKAN {
-        vulkan::negotiation::enable(ctx)?;
+        if let Err(e) = vulkan::negotiation::enable(ctx, validation) {
+            warn!("Couldn't enable the Vulkan context negotiation interface, the frontend will select the device: {e}");
+        }
     }
-
-    // Enable the Vulkan context negotiation interface if using Vulkan,
-    // otherwise do nothing.
-
-    Ok(())
 }
@@ -0,0 +1,76 @@
+use std::sync::{Arc, Weak};
+
+use log::warn;
+use ruffle_render::bitmap::{Bitmap, BitmapHandle, BitmapHandleImpl};
+
+/// Past this many shapes, we warn that the content may be leaking them.
+/// Shapes can't be refused, since `register_shape` is infallible.
+const SHAPE_WARNING_THRESHOLD: usize = 100_000;
+
+/// Tracks how much GPU memory registered bitmaps use,
+/// so pathological content gets a warning before it exhausts it.
+/// Bitmaps are never refused; Ruffle expects registering one to succeed, and panics otherwise.
+pub struct MemoryBudget {
+    /// Bytes of live bitmaps to warn past, or `None` to never warn
+    limit: Option<usize>,
+    bitmaps: Vec<(Weak<dyn BitmapHandleImpl>, usize)>,
+    shapes: usize,
+    warned: bool,
+}
+
+impl MemoryBudget {
+    pub fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            bitmaps: Vec::new(),
+            shapes: 0,
+            warned: false,
+        }
+    }
+
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+        self.warned = false;
+    }
+
+    /// Bytes used by bitmaps that haven't been freed yet.
+    pub fn bitmap_bytes(&mut self) -> usize {
+        self.bitmaps.retain(|(handle, _)| handle.strong_count() > 0);
+        self.bitmaps.iter().map(|(_, size)| size).sum()
+    }
+
+    /// Warns once if registering this bitmap would exceed the limit.
+    pub fn check_bitmap(&mut self, bitmap: &Bitmap) {
+        let limit = match self.limit {
+            Some(limit) => limit,
+            None => return,
+        };
+
+        let requested = Self::bitmap_size(bitmap);
+        let used = self.bitmap_bytes();
+        if used + requested > limit && !self.warned {
+            warn!("Bitmaps are past the {limit}-byte limit ({used} + {requested} bytes), content may run out");
+            self.warned = true;
+        }
+    }
+
+    /// Tracked even without a limit, so one set later counts bitmaps that are already loaded.
+    pub fn track_bitmap(&mut self, handle: &BitmapHandle, bitmap_size: usize) {
+        if self.bitmaps.len() == self.bitmaps.capacity() {
+            // Drop freed bitmaps before growing, so the list doesn't fill up with them when nothing calls bitmap_bytes
+            self.bitmaps.retain(|(bitmap, _)| bitmap.strong_count() > 0);
+        }
+        self.bitmaps.push((Arc::downgrade(&handle.0), bitmap_size));
+    }
+
+    pub fn track_shape(&mut self) {
+        self.shapes += 1;
+        if self.shapes == SHAPE_WARNING_THRESHOLD {
+            warn!("Content has registered {} shapes, it may run out of memory", self.shapes);
+        }
+    }
+
+    pub fn bitmap_size(bitmap: &Bitmap) -> usize {
+        (bitmap.width() as usize) * (bitmap.height() as usize) * 4
+    }
+}
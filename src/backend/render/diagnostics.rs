@@ -0,0 +1,14 @@
+use std::fmt::Write;
+
+use ruffle_render_wgpu::descriptors::Descriptors;
+
+/// Describes the adapter and the features and limits that were actually negotiated,
+/// for attaching to bug reports.
+pub fn adapter_report(descriptors: &Descriptors) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, "Adapter: {:#?}", descriptors.adapter.get_info());
+    let _ = writeln!(report, "Adapter features: {:?}", descriptors.adapter.features());
+    let _ = writeln!(report, "Device features: {:?}", descriptors.device.features());
+    let _ = writeln!(report, "Device limits: {:#?}", descriptors.device.limits());
+    report
+}
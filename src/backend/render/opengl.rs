@@ -6,7 +6,8 @@ use std::ptr;
 use std::sync::Arc;
 
 use gc_arena::MutationContext;
-use log::trace;
+use log::{info, trace};
+use ruffle_core::backend::ui::MouseCursor;
 use ruffle_core::Color;
 use ruffle_core::swf::Glyph;
 use ruffle_render::backend::{Context3D, Context3DCommand, RenderBackend, ShapeHandle, ViewportDimensions};
@@ -21,16 +22,24 @@ use ruffle_render_wgpu::target::TextureTarget;
 use rust_libretro_sys::{retro_game_geometry, retro_hw_render_callback};
 use wgpu_types::InstanceDescriptor;
 
+use crate::backend::render::budget::MemoryBudget;
+use crate::backend::render::diagnostics;
+use crate::backend::render::post_process::PostProcess;
+use crate::options::{GuideOverlay, LetterboxColor, PostProcessPreset};
 use crate::backend::render::wgpu::required_limits;
 
 pub struct OpenGlWgpuRenderBackend {
     backend: WgpuRenderBackend<TextureTarget>,
+    budget: MemoryBudget,
+    post_process: PostProcess,
 }
 
 impl OpenGlWgpuRenderBackend {
     pub async fn new(
         hw_render: &retro_hw_render_callback,
         geometry: &retro_game_geometry,
+        max_bitmap_memory: Option<usize>,
+        post_process: PostProcessPreset,
     ) -> Result<OpenGlWgpuRenderBackend, Box<dyn Error>> {
         let descriptors = unsafe {
             Self::build_descriptors_for_gl(
@@ -49,13 +58,48 @@ impl OpenGlWgpuRenderBackend {
             )
         }.await?;
         let target = TextureTarget::new(&descriptors.device, (geometry.base_width, geometry.max_height))?;
+        let target_format = target.format;
 
         Ok(Self {
             backend: WgpuRenderBackend::new(Arc::new(descriptors), target)?,
             // TODO: Get the sample count from the core config
+            budget: MemoryBudget::new(max_bitmap_memory),
+            post_process: PostProcess::new(target_format, post_process),
         })
     }
 
+    pub fn set_max_bitmap_memory(&mut self, max_bitmap_memory: Option<usize>) {
+        self.budget.set_limit(max_bitmap_memory);
+    }
+
+    pub fn set_post_process(&mut self, preset: PostProcessPreset) {
+        self.post_process.set_preset(preset);
+    }
+
+    pub fn set_overlay(&mut self, overlay: GuideOverlay) {
+        self.post_process.set_overlay(overlay);
+    }
+
+    pub fn set_letterbox(&mut self, bar_color: LetterboxColor, stage_rect: Option<[f32; 4]>) {
+        self.post_process.set_letterbox(bar_color, stage_rect);
+    }
+
+    pub fn set_audio_levels(&mut self, levels: Option<[f32; 2]>) {
+        self.post_process.set_levels(levels);
+    }
+
+    pub fn set_cursor(&mut self, cursor: Option<(MouseCursor, [f32; 2])>) -> bool {
+        self.post_process.set_cursor(cursor)
+    }
+
+    /// wgpu doesn't expose the GL extension list, so this only covers the adapter.
+    pub fn diagnostics(&self) -> String {
+        diagnostics::adapter_report(self.backend.descriptors())
+    }
+
+    /// wgpu wraps the frontend's current GL context rather than picking an adapter, so the GPU is whichever one
+    /// the frontend created that context on; libretro has no way for a core to ask for another.
+    /// On multi-GPU systems, choose the GPU in the frontend or driver settings (or use the Vulkan path).
       async unsafe fn build_descriptors_for_gl(
         fun: impl FnMut(&str) -> *const core::ffi::c_void,
         trace_path: Option<&Path>,
@@ -70,6 +114,9 @@ impl OpenGlWgpuRenderBackend {
         let adapter_hal =
             <Gles as Api>::Adapter::new_external(fun).expect("expose_adapter should be infallible");
         let adapter = instance.create_adapter_from_hal(adapter_hal);
+        let adapter_info = adapter.get_info();
+        // On GL, wgpu reports GL_RENDERER as the name and GL_VERSION as the driver info
+        info!("Using the frontend's GL context on {:?} ({:?})", adapter_info.name, adapter_info.driver_info);
         let (limits, features) = required_limits(&adapter);
         let (device, queue) = adapter
             .request_device(
@@ -96,15 +143,23 @@ impl RenderBackend for OpenGlWgpuRenderBackend {
     }
 
     fn register_shape(&mut self, shape: DistilledShape, bitmap_source: &dyn BitmapSource) -> ShapeHandle {
+        self.budget.track_shape();
         self.backend.register_shape(shape, bitmap_source)
     }
 
     fn submit_frame(&mut self, clear: Color, commands: CommandList) {
-        self.backend.submit_frame(clear, commands)
+        self.backend.submit_frame(clear, commands);
+        let descriptors = self.backend.descriptors().clone();
+        self.post_process
+            .apply(&descriptors.device, &descriptors.queue, &self.backend.target().texture, None, clear);
     }
 
     fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, RuffleError> {
-        self.backend.register_bitmap(bitmap)
+        self.budget.check_bitmap(&bitmap);
+        let size = MemoryBudget::bitmap_size(&bitmap);
+        let handle = self.backend.register_bitmap(bitmap)?;
+        self.budget.track_bitmap(&handle, size);
+        Ok(handle)
     }
 
     fn create_context3d(&mut self) -> Result<Box<dyn Context3D>, RuffleError> {
@@ -0,0 +1,324 @@
+use bytemuck::{Pod, Zeroable};
+use ruffle_core::backend::ui::MouseCursor;
+use ruffle_core::Color;
+use wgpu::util::DeviceExt;
+
+use crate::options::{GuideOverlay, LetterboxColor, PostProcessPreset};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Pod, Zeroable)]
+struct Uniforms {
+    preset: u32,
+    fill_bars: u32,
+    size: [f32; 2],
+    /// x, y, width, height in pixels
+    stage_rect: [f32; 4],
+    bar_color: [f32; 4],
+    overlay: u32,
+    _padding0: u32,
+    /// Size of the frame being sampled, larger than `size` when supersampling
+    source_size: [f32; 2],
+    /// Peak level of the left and right audio channels, from 0.0 to 1.0
+    levels: [f32; 2],
+    meters: u32,
+    _padding1: u32,
+    /// Where the cursor points, in pixels
+    cursor: [f32; 2],
+    cursor_shape: u32,
+    _padding2: u32,
+}
+
+/// GPU objects for the post-process pass, created the first time a preset is used.
+struct Resources {
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniforms: wgpu::Buffer,
+    /// Copy of the frame to sample from, since a texture can't be read and written in the same pass
+    scratch: Option<wgpu::Texture>,
+}
+
+/// Applies an optional full-screen shader to the render target after Ruffle has drawn to it.
+/// The same pass recolors the letterbox bars around the stage, draws layout guides, audio level meters,
+/// and the mouse cursor over it, and shrinks supersampled frames to the size the frontend expects.
+pub struct PostProcess {
+    preset: PostProcessPreset,
+    overlay: GuideOverlay,
+    bar_color: LetterboxColor,
+    /// Where the stage sits in the render target (x, y, width, height), or `None` if it isn't letterboxed
+    stage_rect: Option<[f32; 4]>,
+    /// Peak audio levels to draw meters for, or `None` to hide the meters
+    levels: Option<[f32; 2]>,
+    /// The cursor to draw and where it points in the render target, or `None` to hide it
+    cursor: Option<(MouseCursor, [f32; 2])>,
+    format: wgpu::TextureFormat,
+    resources: Option<Resources>,
+}
+
+impl PostProcess {
+    pub fn new(format: wgpu::TextureFormat, preset: PostProcessPreset) -> Self {
+        Self {
+            preset,
+            overlay: GuideOverlay::Off,
+            bar_color: LetterboxColor::Black,
+            stage_rect: None,
+            levels: None,
+            cursor: None,
+            format,
+            resources: None,
+        }
+    }
+
+    pub fn set_preset(&mut self, preset: PostProcessPreset) {
+        self.preset = preset;
+    }
+
+    pub fn set_overlay(&mut self, overlay: GuideOverlay) {
+        self.overlay = overlay;
+    }
+
+    pub fn set_letterbox(&mut self, bar_color: LetterboxColor, stage_rect: Option<[f32; 4]>) {
+        self.bar_color = bar_color;
+        self.stage_rect = stage_rect;
+    }
+
+    pub fn set_levels(&mut self, levels: Option<[f32; 2]>) {
+        self.levels = levels;
+    }
+
+    /// Returns whether the cursor changed, since the frame has to be drawn again to show it.
+    pub fn set_cursor(&mut self, cursor: Option<(MouseCursor, [f32; 2])>) -> bool {
+        let changed = self.cursor != cursor;
+        self.cursor = cursor;
+        changed
+    }
+
+    /// Runs the pass from `target` into `output`, scaling it down to fit if `output` is smaller,
+    /// or in place on `target` if there's no `output`. `target` must have been created with
+    /// `TEXTURE_BINDING` usage, and also `COPY_SRC` and `RENDER_ATTACHMENT` if it's processed in place.
+    /// `clear` is the stage's background color.
+    /// Does nothing in place if post-processing, guides, meters, and the cursor are off
+    /// and the bars don't need recoloring.
+    pub fn apply(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        target: &wgpu::Texture,
+        output: Option<&wgpu::Texture>,
+        clear: Color,
+    ) {
+        let bar_color = match (self.bar_color, self.stage_rect) {
+            (LetterboxColor::Black, _) | (_, None) => None,
+            (LetterboxColor::Stage, Some(_)) => Some([clear.r, clear.g, clear.b]),
+            (LetterboxColor::Custom(color), Some(_)) => Some(color),
+        };
+        let preset = match (self.preset.shader_index(), bar_color, self.overlay, self.levels, self.cursor, output) {
+            (Some(preset), _, _, _, _, _) => preset,
+            (None, None, GuideOverlay::Off, None, None, None) => return,
+            (None, _, _, _, _, _) => 0, // Passthrough, just for the bars, guides, meters, cursor, or downsampling
+        };
+
+        #[cfg(feature = "profiler")]
+        profiling::scope!("PostProcess::apply");
+        let format = self.format;
+        let resources = self.resources.get_or_insert_with(|| Resources::new(device, format));
+        let source_size = target.size();
+        let size = output.map_or(source_size, wgpu::Texture::size);
+        let stale = |scratch: &wgpu::Texture| scratch.size() != size;
+        if output.is_none() && resources.scratch.as_ref().map_or(true, stale) {
+            resources.scratch = Some(device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("Ruffle Post-Process Source"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                view_formats: &[format],
+                usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING,
+            }));
+        }
+        // Can't sample the texture being drawn to, so work from a copy when there's nowhere else to draw
+        let source = match output {
+            Some(_) => target,
+            None => resources.scratch.as_ref().unwrap(),
+        };
+
+        let [r, g, b] = bar_color.unwrap_or_default();
+        // The stage's position is in the render target's pixels, but the shader works in the output's
+        let (scale_x, scale_y) = (
+            size.width as f32 / source_size.width as f32,
+            size.height as f32 / source_size.height as f32,
+        );
+        let uniforms = Uniforms {
+            preset,
+            fill_bars: bar_color.is_some() as u32,
+            size: [size.width as f32, size.height as f32],
+            // Guides cover the whole target if the stage does
+            stage_rect: self
+                .stage_rect
+                .map(|[x, y, width, height]| [x * scale_x, y * scale_y, width * scale_x, height * scale_y])
+                .unwrap_or([0.0, 0.0, size.width as f32, size.height as f32]),
+            bar_color: [f32::from(r) / 255.0, f32::from(g) / 255.0, f32::from(b) / 255.0, 1.0],
+            overlay: self.overlay.shader_index(),
+            _padding0: 0,
+            source_size: [source_size.width as f32, source_size.height as f32],
+            levels: self.levels.unwrap_or_default(),
+            meters: self.levels.is_some() as u32,
+            _padding1: 0,
+            cursor: self.cursor.map_or([0.0; 2], |(_, [x, y])| [x * scale_x, y * scale_y]),
+            cursor_shape: self.cursor.map_or(0, |(cursor, _)| cursor_shader_index(cursor)),
+            _padding2: 0,
+        };
+        queue.write_buffer(&resources.uniforms, 0, bytemuck::bytes_of(&uniforms));
+
+        let source_view = source.create_view(&Default::default());
+        let target_view = output.unwrap_or(target).create_view(&Default::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Ruffle Post-Process"),
+            layout: &resources.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: resources.uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&source_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&resources.sampler),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Ruffle Post-Process"),
+        });
+        if output.is_none() {
+            encoder.copy_texture_to_texture(target.as_image_copy(), source.as_image_copy(), size);
+        }
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Ruffle Post-Process"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &target_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            pass.set_pipeline(&resources.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+
+        queue.submit(Some(encoder.finish()));
+    }
+}
+
+/// Keep in sync with the CURSOR_ numbers in post_process.wgsl, where 0 means no cursor.
+fn cursor_shader_index(cursor: MouseCursor) -> u32 {
+    match cursor {
+        MouseCursor::Arrow => 1,
+        MouseCursor::Hand => 2,
+        MouseCursor::IBeam => 3,
+        MouseCursor::Grab => 4,
+    }
+}
+
+impl Resources {
+    fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        #[cfg(feature = "profiler")]
+        profiling::scope!("PostProcess::Resources::new");
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Ruffle Post-Process"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/post_process.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Ruffle Post-Process"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Ruffle Post-Process"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Ruffle Post-Process"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "main_vertex",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "main_fragment",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Ruffle Post-Process"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ruffle Post-Process Uniforms"),
+            contents: bytemuck::bytes_of(&Uniforms::zeroed()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        Self {
+            pipeline,
+            bind_group_layout,
+            sampler,
+            uniforms,
+            scratch: None,
+        }
+    }
+}
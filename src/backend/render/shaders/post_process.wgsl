@@ -0,0 +1,231 @@
+// Full-screen post-process applied to Ruffle's output before it's handed to the frontend.
+// Keep the preset, overlay, and cursor numbers in sync with PostProcessPreset::shader_index,
+// GuideOverlay::shader_index, and cursor_shader_index.
+
+struct Uniforms {
+    preset: u32,
+    fill_bars: u32,
+    size: vec2<f32>,
+    // x, y, width, height in pixels
+    stage_rect: vec4<f32>,
+    bar_color: vec4<f32>,
+    overlay: u32,
+    _padding0: u32,
+    // Larger than size when supersampling
+    source_size: vec2<f32>,
+    // Peak level of the left and right audio channels, from 0.0 to 1.0
+    levels: vec2<f32>,
+    meters: u32,
+    _padding1: u32,
+    // Where the cursor points, in pixels
+    cursor: vec2<f32>,
+    cursor_shape: u32,
+    _padding2: u32,
+};
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var source: texture_2d<f32>;
+@group(0) @binding(2) var source_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+const PRESET_NONE: u32 = 0u;
+const PRESET_CRT: u32 = 2u;
+
+const OVERLAY_SAFE_AREA: u32 = 1u;
+const OVERLAY_THIRDS: u32 = 2u;
+const OVERLAY_GRID: u32 = 3u;
+
+// Guide lines are about this many pixels wide, whatever the stage's scale
+const GUIDE_WIDTH: f32 = 1.0;
+const GUIDE_COLOR: vec3<f32> = vec3<f32>(1.0, 0.0, 1.0);
+const GUIDE_OPACITY: f32 = 0.6;
+
+// Audio level meters are bars this many pixels wide, this far from the left edge and each other
+const METER_WIDTH: f32 = 6.0;
+const METER_MARGIN: f32 = 4.0;
+const METER_QUIET: vec3<f32> = vec3<f32>(0.0, 1.0, 0.0);
+const METER_LOUD: vec3<f32> = vec3<f32>(1.0, 0.0, 0.0);
+const METER_OPACITY: f32 = 0.8;
+
+const CURSOR_ARROW: u32 = 1u;
+const CURSOR_HAND: u32 = 2u;
+const CURSOR_IBEAM: u32 = 3u;
+const CURSOR_GRAB: u32 = 4u;
+
+// The cursor is white with a black outline this many pixels wide, so it shows up on any background
+const CURSOR_OUTLINE: f32 = 1.0;
+
+// A single triangle that covers the whole target
+@vertex
+fn main_vertex(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+// Averages the source pixels that fall within one output pixel, so supersampled frames shrink without aliasing
+fn sample_source(uv: vec2<f32>) -> vec4<f32> {
+    let taps = vec2<u32>(max(ceil(uniforms.source_size / uniforms.size - 0.001), vec2<f32>(1.0)));
+    var total = vec4<f32>(0.0);
+    for (var y = 0u; y < taps.y; y++) {
+        for (var x = 0u; x < taps.x; x++) {
+            let offset = (vec2<f32>(f32(x), f32(y)) + 0.5) / vec2<f32>(taps) - 0.5;
+            // textureSampleLevel, since textureSample isn't allowed in non-uniform control flow
+            total += textureSampleLevel(source, source_sampler, uv + offset / uniforms.size, 0.0);
+        }
+    }
+    return total / f32(taps.x * taps.y);
+}
+
+// Darkens the gaps between rows of pixels
+fn scanlines(uv: vec2<f32>, color: vec3<f32>) -> vec3<f32> {
+    let row = uv.y * uniforms.size.y;
+    return color * (0.8 + 0.2 * cos(row * 6.2831853));
+}
+
+// Whether `pixel` is on one of `divisions` evenly spaced lines across the stage, edges included
+fn on_grid(pixel: vec2<f32>, divisions: f32) -> bool {
+    let stage = (pixel - uniforms.stage_rect.xy) / uniforms.stage_rect.zw * divisions;
+    let distance = abs(fract(stage + 0.5) - 0.5) / divisions * uniforms.stage_rect.zw;
+    let inside = all(stage >= vec2<f32>(0.0)) && all(stage <= vec2<f32>(divisions));
+    return inside && any(distance < vec2<f32>(GUIDE_WIDTH * 0.5));
+}
+
+// Whether `pixel` is on the outline of the stage inset by `margin` (a fraction of its size) on every side
+fn on_inset(pixel: vec2<f32>, margin: f32) -> bool {
+    let inset_min = uniforms.stage_rect.xy + uniforms.stage_rect.zw * margin;
+    let inset_max = uniforms.stage_rect.xy + uniforms.stage_rect.zw * (1.0 - margin);
+    let inside = all(pixel >= inset_min - GUIDE_WIDTH) && all(pixel <= inset_max + GUIDE_WIDTH);
+    let edge = min(abs(pixel - inset_min), abs(pixel - inset_max));
+    return inside && any(edge < vec2<f32>(GUIDE_WIDTH * 0.5));
+}
+
+fn on_guide(pixel: vec2<f32>) -> bool {
+    switch (uniforms.overlay) {
+        case OVERLAY_SAFE_AREA: {
+            return on_inset(pixel, 0.05) || on_inset(pixel, 0.1);
+        }
+        case OVERLAY_THIRDS: {
+            return on_grid(pixel, 3.0);
+        }
+        case OVERLAY_GRID: {
+            return on_grid(pixel, 10.0);
+        }
+        default: {
+            return false;
+        }
+    }
+}
+
+// The color of the audio level meter at `pixel` (transparent if there isn't one);
+// the left channel's meter is drawn first, filling up from the bottom
+fn meter_color(pixel: vec2<f32>) -> vec4<f32> {
+    let top = METER_MARGIN;
+    let bottom = uniforms.size.y - METER_MARGIN;
+    let slot = (pixel.x - METER_MARGIN) / (METER_WIDTH + METER_MARGIN);
+    let on_bar = fract(slot) * (METER_WIDTH + METER_MARGIN) < METER_WIDTH;
+    if (uniforms.meters == 0u || slot < 0.0 || slot >= 2.0 || !on_bar || pixel.y < top || pixel.y > bottom) {
+        return vec4<f32>(0.0);
+    }
+
+    let level = select(uniforms.levels.x, uniforms.levels.y, slot >= 1.0);
+    let height = (bottom - pixel.y) / (bottom - top);
+    if (height > level) {
+        // The empty part of the meter, so it's visible even in silence
+        return vec4<f32>(0.0, 0.0, 0.0, METER_OPACITY);
+    }
+
+    // Green when quiet, red near clipping
+    return vec4<f32>(mix(METER_QUIET, METER_LOUD, height), METER_OPACITY);
+}
+
+// Whether `offset` (from where the cursor points) is within the cursor's shape, grown by `grow` pixels
+fn in_cursor(offset: vec2<f32>, grow: f32) -> bool {
+    switch (uniforms.cursor_shape) {
+        case CURSOR_ARROW: {
+            // A triangle pointing up and to the left, with its tip at the offset's origin
+            let bottom_normal = normalize(vec2<f32>(4.7, 11.3));
+            return offset.x >= -grow
+                && (offset.x - offset.y) * 0.7071068 <= grow
+                && dot(offset - vec2<f32>(0.0, 16.0), bottom_normal) <= grow;
+        }
+        case CURSOR_HAND: {
+            // A dot, to show there's something to click
+            return length(offset) <= 5.0 + grow;
+        }
+        case CURSOR_IBEAM: {
+            let stem = abs(offset.x) <= 1.0 + grow && abs(offset.y) <= 8.0 + grow;
+            let serifs = abs(offset.x) <= 3.0 + grow && abs(abs(offset.y) - 8.0) <= 1.0 + grow;
+            return stem || serifs;
+        }
+        case CURSOR_GRAB: {
+            // A ring, like the dot but open
+            return abs(length(offset) - 5.0) <= 1.5 + grow;
+        }
+        default: {
+            return false;
+        }
+    }
+}
+
+// The color of the cursor at `pixel` (transparent if it isn't there)
+fn cursor_color(pixel: vec2<f32>) -> vec4<f32> {
+    let offset = pixel - uniforms.cursor;
+    if (in_cursor(offset, 0.0)) {
+        return vec4<f32>(1.0);
+    }
+    if (in_cursor(offset, CURSOR_OUTLINE)) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+    return vec4<f32>(0.0);
+}
+
+@fragment
+fn main_fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let centered = in.uv * 2.0 - 1.0;
+    var uv = in.uv;
+    if (uniforms.preset == PRESET_CRT) {
+        // Barrel distortion, like the curved glass of a CRT
+        uv = centered * (1.0 + 0.06 * dot(centered, centered)) * 0.5 + 0.5;
+    }
+
+    var color = sample_source(uv);
+    if (uniforms.fill_bars != 0u) {
+        let pixel = uv * uniforms.size;
+        let stage_min = uniforms.stage_rect.xy;
+        let stage_max = uniforms.stage_rect.xy + uniforms.stage_rect.zw;
+        if (any(pixel < stage_min) || any(pixel >= stage_max)) {
+            color = uniforms.bar_color;
+        }
+    }
+
+    var rgb = color.rgb;
+    if (uniforms.preset != PRESET_NONE) {
+        rgb = scanlines(uv, rgb);
+    }
+
+    if (uniforms.preset == PRESET_CRT) {
+        let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+        let vignette = smoothstep(1.6, 0.7, length(centered));
+        rgb = select(vec3<f32>(0.0), rgb * vignette, inside);
+    }
+
+    // Drawn last and without distortion, so the guides line up with the stage as the frontend shows it
+    if (on_guide(in.uv * uniforms.size)) {
+        rgb = mix(rgb, GUIDE_COLOR, GUIDE_OPACITY);
+    }
+
+    let meter = meter_color(in.uv * uniforms.size);
+    rgb = mix(rgb, meter.rgb, meter.a);
+
+    let cursor = cursor_color(in.uv * uniforms.size);
+    rgb = mix(rgb, cursor.rgb, cursor.a);
+
+    return vec4<f32>(rgb, color.a);
+}
@@ -0,0 +1,206 @@
+use std::borrow::Cow;
+use std::error::Error;
+
+use log::{info, warn};
+use ruffle_core::Color;
+use ruffle_render::backend::{Context3D, RenderBackend, ShapeHandle, ViewportDimensions};
+use ruffle_render::bitmap::{Bitmap, BitmapHandle, BitmapSource, PixelRegion, SyncHandle};
+use ruffle_render::commands::CommandList;
+use ruffle_render::error::Error as RuffleError;
+use ruffle_render::filters::Filter;
+use ruffle_render::quality::StageQuality;
+use ruffle_render::shape_utils::DistilledShape;
+use ruffle_render_wgpu::backend::WgpuRenderBackend;
+use ruffle_render_wgpu::target::TextureTarget;
+use rust_libretro_sys::retro_game_geometry;
+use wgpu_types::InstanceDescriptor;
+
+use crate::backend::render::budget::MemoryBudget;
+
+/// Renders offscreen with whatever wgpu backend is available,
+/// then copies each frame back to the CPU for frontends without a usable hardware context.
+pub struct SoftwareWgpuRenderBackend {
+    backend: WgpuRenderBackend<TextureTarget>,
+    budget: MemoryBudget,
+    /// The last captured frame, in XRGB8888
+    frame: Vec<u8>,
+    /// The size of the frames the frontend expects, which the render target exceeds when supersampling
+    output_size: (u32, u32),
+}
+
+impl SoftwareWgpuRenderBackend {
+    pub fn new(
+        geometry: &retro_game_geometry,
+        backends: wgpu::Backends,
+        max_bitmap_memory: Option<usize>,
+    ) -> Result<Self, Box<dyn Error>> {
+        #[cfg(feature = "profiler")]
+        profiling::scope!("SoftwareWgpuRenderBackend::new");
+        let backends = Self::available_backends(backends);
+        let backend = WgpuRenderBackend::for_offscreen(
+            (geometry.base_width, geometry.base_height),
+            backends,
+            wgpu::PowerPreference::LowPower,
+            None,
+        )?;
+        info!("Using fallback renderer: {}", backend.debug_info());
+
+        Ok(Self {
+            backend,
+            budget: MemoryBudget::new(max_bitmap_memory),
+            frame: Vec::new(),
+            output_size: (geometry.base_width, geometry.base_height),
+        })
+    }
+
+    /// Returns `requested` if any adapter supports it, or every backend otherwise.
+    fn available_backends(requested: wgpu::Backends) -> wgpu::Backends {
+        let instance = wgpu::Instance::new(InstanceDescriptor {
+            backends: requested,
+            dx12_shader_compiler: Default::default(),
+        });
+
+        if instance.enumerate_adapters(requested).next().is_some() {
+            requested
+        } else {
+            warn!("No adapter supports {requested:?}, letting wgpu pick a backend instead");
+            wgpu::Backends::all()
+        }
+    }
+
+    pub fn set_max_bitmap_memory(&mut self, max_bitmap_memory: Option<usize>) {
+        self.budget.set_limit(max_bitmap_memory);
+    }
+
+    /// Copies the most recently rendered frame to the CPU as XRGB8888,
+    /// scaling it down to the frontend's size if it was supersampled,
+    /// and returns the pixels and their dimensions.
+    pub fn capture_frame(&mut self) -> Option<(&[u8], u32, u32)> {
+        #[cfg(feature = "profiler")]
+        profiling::scope!("SoftwareWgpuRenderBackend::capture_frame");
+        let image = self.backend.capture_frame()?;
+        let (source_width, source_height) = (image.width(), image.height());
+        let (width, height) = self.output_size;
+
+        self.frame.clear();
+        if (source_width, source_height) == (width, height) {
+            self.frame.extend(
+                image
+                    .as_raw()
+                    .chunks_exact(4)
+                    .flat_map(|rgba| [rgba[2], rgba[1], rgba[0], 0xFF]),
+            );
+        } else {
+            // Average the block of source pixels under each output pixel
+            let pixels = image.as_raw();
+            for y in 0..height {
+                let (top, bottom) = Self::source_span(y, height, source_height);
+                for x in 0..width {
+                    let (left, right) = Self::source_span(x, width, source_width);
+                    let mut total = [0u32; 3];
+                    for source_y in top..bottom {
+                        let row = (source_y * source_width) as usize * 4;
+                        for source_x in left..right {
+                            let rgba = &pixels[row + source_x as usize * 4..][..4];
+                            total[0] += u32::from(rgba[0]);
+                            total[1] += u32::from(rgba[1]);
+                            total[2] += u32::from(rgba[2]);
+                        }
+                    }
+                    let count = (bottom - top) * (right - left);
+                    let [r, g, b] = total.map(|channel| ((channel + count / 2) / count) as u8);
+                    self.frame.extend([b, g, r, 0xFF]);
+                }
+            }
+        }
+
+        Some((&self.frame, width, height))
+    }
+
+    /// The range of source pixels along one axis that output pixel `index` covers, always at least one.
+    fn source_span(index: u32, output_length: u32, source_length: u32) -> (u32, u32) {
+        let scale = |index: u32| (u64::from(index) * u64::from(source_length) / u64::from(output_length)) as u32;
+        let start = scale(index).min(source_length - 1);
+        (start, scale(index + 1).clamp(start + 1, source_length))
+    }
+}
+
+impl RenderBackend for SoftwareWgpuRenderBackend {
+    fn viewport_dimensions(&self) -> ViewportDimensions {
+        self.backend.viewport_dimensions()
+    }
+
+    fn set_viewport_dimensions(&mut self, dimensions: ViewportDimensions) {
+        self.backend.set_viewport_dimensions(dimensions)
+    }
+
+    fn register_shape(&mut self, shape: DistilledShape, bitmap_source: &dyn BitmapSource) -> ShapeHandle {
+        self.budget.track_shape();
+        self.backend.register_shape(shape, bitmap_source)
+    }
+
+    fn submit_frame(&mut self, clear: Color, commands: CommandList) {
+        #[cfg(feature = "profiler")]
+        profiling::scope!("SoftwareWgpuRenderBackend::submit_frame");
+        self.backend.submit_frame(clear, commands)
+    }
+
+    fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, RuffleError> {
+        self.budget.check_bitmap(&bitmap);
+        let size = MemoryBudget::bitmap_size(&bitmap);
+        let handle = self.backend.register_bitmap(bitmap)?;
+        self.budget.track_bitmap(&handle, size);
+        Ok(handle)
+    }
+
+    fn create_context3d(&mut self) -> Result<Box<dyn Context3D>, RuffleError> {
+        self.backend.create_context3d()
+    }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        self.backend.set_quality(quality)
+    }
+
+    fn name(&self) -> &'static str {
+        "Fallback (wgpu)"
+    }
+
+    fn render_offscreen(
+        &mut self,
+        handle: BitmapHandle,
+        commands: CommandList,
+        quality: StageQuality,
+        bounds: PixelRegion,
+    ) -> Option<Box<dyn SyncHandle>> {
+        self.backend.render_offscreen(handle, commands, quality, bounds)
+    }
+
+    fn update_texture(&mut self, handle: &BitmapHandle, bitmap: Bitmap, region: PixelRegion) -> Result<(), RuffleError> {
+        self.backend.update_texture(handle, bitmap, region)
+    }
+
+    fn context3d_present(&mut self, context: &mut dyn Context3D) -> Result<(), RuffleError> {
+        self.backend.context3d_present(context)
+    }
+
+    fn debug_info(&self) -> Cow<'static, str> {
+        self.backend.debug_info()
+    }
+
+    fn apply_filter(
+        &mut self,
+        source: BitmapHandle,
+        source_point: (u32, u32),
+        source_size: (u32, u32),
+        destination: BitmapHandle,
+        dest_point: (u32, u32),
+        filter: Filter,
+    ) -> Option<Box<dyn SyncHandle>> {
+        self.backend
+            .apply_filter(source, source_point, source_size, destination, dest_point, filter)
+    }
+
+    fn is_filter_supported(&self, filter: &Filter) -> bool {
+        self.backend.is_filter_supported(filter)
+    }
+}
@@ -1,9 +1,11 @@
 use std::borrow::Cow;
 use std::error::Error;
+use std::fmt::Write;
 use std::sync::Arc;
 
 use gc_arena::MutationContext;
 use log::{debug, warn};
+use ruffle_core::backend::ui::MouseCursor;
 use ruffle_core::swf::Glyph;
 use ruffle_core::Color;
 use ruffle_render::backend::{Context3D, Context3DCommand, RenderBackend, ShapeHandle, ViewportDimensions};
@@ -15,13 +17,18 @@ use ruffle_render::quality::StageQuality;
 use ruffle_render::shape_utils::DistilledShape;
 use ruffle_render_wgpu::backend::WgpuRenderBackend;
 use ruffle_render_wgpu::descriptors::Descriptors;
+use ruffle_render_wgpu::target::RenderTarget;
 use rust_libretro_sys::{retro_game_geometry, retro_hw_render_interface_vulkan};
 use wgpu_hal::api::Vulkan;
 
+use crate::backend::render::budget::MemoryBudget;
+use crate::backend::render::diagnostics;
+use crate::backend::render::post_process::PostProcess;
 use crate::backend::render::vulkan::render_interface::VulkanRenderInterface;
+use crate::options::{GuideOverlay, LetterboxColor, PostProcessPreset};
 
 use self::target::RetroTextureTarget;
-use self::util::create_descriptors;
+use self::util::{create_descriptors, PropertiesFormat};
 
 mod global;
 pub mod negotiation;
@@ -32,21 +39,46 @@ mod util;
 pub struct VulkanWgpuRenderBackend {
     backend: WgpuRenderBackend<RetroTextureTarget>,
     interface: VulkanRenderInterface,
+    /// The family of the queue we render on, which the frontend must take the image's ownership from
+    queue_family_index: u32,
     descriptors: Arc<Descriptors>,
+    /// Maximum number of frames that may be queued on the GPU, or `None` to leave it to the frontend
+    frame_queue_depth: Option<u32>,
+    frames_in_flight: u32,
+    budget: MemoryBudget,
+    post_process: PostProcess,
+    /// The size of the frames the frontend expects, which the render target exceeds when supersampling
+    output_size: (u32, u32),
+    /// Where supersampled frames are scaled down to for the frontend, created the first time one is rendered
+    present: Option<RetroTextureTarget>,
 }
 
 impl VulkanWgpuRenderBackend {
     pub fn new(
         geometry: &retro_game_geometry,
         hw_render: &retro_hw_render_interface_vulkan,
+        frame_queue_depth: Option<u32>,
+        max_bitmap_memory: Option<usize>,
+        post_process: PostProcessPreset,
     ) -> Result<Self, Box<dyn Error>> {
         #[cfg(feature = "profiler")]
         profiling::scope!("VulkanWgpuRenderBackend::new");
         let interface = VulkanRenderInterface::new(hw_render)?;
 
         unsafe {
+            if !global::instance_matches(interface.instance()) {
+                // The frontend created the context without negotiating with us,
+                // or what we have is left over from an earlier context
+                global::reset();
+                negotiation::adopt_frontend_instance(hw_render)?;
+            }
+
             let instance = global::INSTANCE.as_ref().unwrap();
-            let descriptors = create_descriptors(instance, &interface)?;
+            // Rendering and handing the image back must agree on the family, or the ownership transfer is wrong.
+            // Without negotiation, what the frontend reports is all there is to go on;
+            // create_descriptors checks that it names a family the device actually has
+            let queue_family_index = global::QUEUE_FAMILY_INDEX.unwrap_or_else(|| interface.queue_family_index());
+            let descriptors = create_descriptors(instance, &interface, queue_family_index)?;
             let (width, height) = (geometry.base_width, geometry.base_height);
             let target =
                 RetroTextureTarget::new(&descriptors.device, (width, height), wgpu::TextureFormat::Rgba8Unorm)?;
@@ -56,10 +88,104 @@ impl VulkanWgpuRenderBackend {
             Ok(Self {
                 backend,
                 interface,
+                queue_family_index,
                 descriptors,
+                frame_queue_depth,
+                frames_in_flight: 0,
+                budget: MemoryBudget::new(max_bitmap_memory),
+                post_process: PostProcess::new(wgpu::TextureFormat::Rgba8Unorm, post_process),
+                output_size: (width, height),
+                present: None,
             })
         }
     }
+
+    pub fn set_frame_queue_depth(&mut self, frame_queue_depth: Option<u32>) {
+        self.frame_queue_depth = frame_queue_depth;
+        self.frames_in_flight = 0;
+    }
+
+    pub fn set_max_bitmap_memory(&mut self, max_bitmap_memory: Option<usize>) {
+        self.budget.set_limit(max_bitmap_memory);
+    }
+
+    pub fn set_post_process(&mut self, preset: PostProcessPreset) {
+        self.post_process.set_preset(preset);
+    }
+
+    pub fn set_overlay(&mut self, overlay: GuideOverlay) {
+        self.post_process.set_overlay(overlay);
+    }
+
+    pub fn set_letterbox(&mut self, bar_color: LetterboxColor, stage_rect: Option<[f32; 4]>) {
+        self.post_process.set_letterbox(bar_color, stage_rect);
+    }
+
+    pub fn set_audio_levels(&mut self, levels: Option<[f32; 2]>) {
+        self.post_process.set_levels(levels);
+    }
+
+    pub fn set_cursor(&mut self, cursor: Option<(MouseCursor, [f32; 2])>) -> bool {
+        self.post_process.set_cursor(cursor)
+    }
+
+    /// Lists the instance and device extensions alongside the adapter details.
+    pub fn diagnostics(&self) -> String {
+        let mut report = diagnostics::adapter_report(&self.descriptors);
+
+        unsafe {
+            if let Some(entry) = global::ENTRY.as_ref() {
+                match entry.enumerate_instance_extension_properties(None) {
+                    Ok(extensions) => {
+                        let _ = writeln!(report, "Instance extensions: {:#?}", PropertiesFormat::new(&extensions));
+                    }
+                    Err(error) => {
+                        let _ = writeln!(report, "Failed to query instance extensions: {error}");
+                    }
+                }
+
+                match entry.enumerate_instance_layer_properties() {
+                    Ok(layers) => {
+                        let _ = writeln!(report, "Instance layers: {:#?}", PropertiesFormat::new(&layers));
+                    }
+                    Err(error) => {
+                        let _ = writeln!(report, "Failed to query instance layers: {error}");
+                    }
+                }
+            }
+
+            if let Some(instance) = global::INSTANCE.as_ref().and_then(|i| i.as_hal::<Vulkan>()) {
+                let instance = instance.shared_instance().raw_instance();
+                match instance.enumerate_device_extension_properties(self.interface.gpu()) {
+                    Ok(extensions) => {
+                        let _ = writeln!(report, "Device extensions: {:#?}", PropertiesFormat::new(&extensions));
+                    }
+                    Err(error) => {
+                        let _ = writeln!(report, "Failed to query device extensions: {error}");
+                    }
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Blocks until the GPU catches up if the configured number of frames are already queued.
+    /// The depth can't exceed the number of sync indices the frontend provides.
+    fn throttle_frame_queue(&mut self) {
+        let depth = match self.frame_queue_depth {
+            Some(depth) => depth.clamp(1, self.interface.get_sync_index_mask().count_ones().max(1)),
+            None => return,
+        };
+
+        self.frames_in_flight += 1;
+        if self.frames_in_flight >= depth {
+            #[cfg(feature = "profiler")]
+            profiling::scope!("wgpu::Device::poll");
+            self.descriptors.device.poll(wgpu::Maintain::Wait);
+            self.frames_in_flight = 0;
+        }
+    }
 }
 
 impl RenderBackend for VulkanWgpuRenderBackend {
@@ -76,6 +202,7 @@ impl RenderBackend for VulkanWgpuRenderBackend {
     fn register_shape(&mut self, shape: DistilledShape, bitmap_source: &dyn BitmapSource) -> ShapeHandle {
         #[cfg(feature = "profiler")]
         profiling::scope!("VulkanWgpuRenderBackend::register_shape");
+        self.budget.track_shape();
         self.backend.register_shape(shape, bitmap_source)
     }
 
@@ -115,14 +242,39 @@ impl RenderBackend for VulkanWgpuRenderBackend {
         profiling::scope!("VulkanWgpuRenderBackend::submit_frame");
         self.backend.submit_frame(clear, commands);
         let target = self.backend.target();
-        let queue_index = self.interface.queue_index();
-        self.interface.set_image(target.get_retro_image(), &[], queue_index);
+        let device = &self.descriptors.device;
+        let present = if (target.width(), target.height()) == self.output_size {
+            None
+        } else {
+            if self.present.is_none() {
+                match RetroTextureTarget::new(device, self.output_size, wgpu::TextureFormat::Rgba8Unorm) {
+                    Ok(present) => self.present = Some(present),
+                    Err(e) => warn!("Can't create an image to downsample frames into: {e}"),
+                }
+            }
+            self.present.as_ref()
+        };
+
+        self.post_process.apply(
+            device,
+            &self.descriptors.queue,
+            target.get_texture(),
+            present.map(RetroTextureTarget::get_texture),
+            clear,
+        );
+        let image = present.unwrap_or(target).get_retro_image();
+        self.interface.set_image(image, &[], self.queue_family_index);
+        self.throttle_frame_queue();
     }
 
     fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, RuffleError> {
         #[cfg(feature = "profiler")]
         profiling::scope!("VulkanWgpuRenderBackend::register_bitmap");
-        self.backend.register_bitmap(bitmap)
+        self.budget.check_bitmap(&bitmap);
+        let size = MemoryBudget::bitmap_size(&bitmap);
+        let handle = self.backend.register_bitmap(bitmap)?;
+        self.budget.track_bitmap(&handle, size);
+        Ok(handle)
     }
 
     fn update_texture(
@@ -174,12 +326,10 @@ impl Drop for VulkanWgpuRenderBackend {
         #[cfg(feature = "profiler")]
         profiling::scope!("VulkanWgpuRenderBackend::drop");
         unsafe {
-            if global::DEVICE.is_none() {
-                return;
-            }
-
             {
-                let device = global::DEVICE.as_ref().unwrap();
+                // Not global::DEVICE; that's unset if the frontend created the device without negotiation
+                let device = &self.descriptors.device;
+                let device = device.as_hal::<Vulkan, _, _>(|c| c.unwrap().raw_device().clone());
                 {
                     #[cfg(feature = "profiler")]
                     profiling::scope!("vkDeviceWaitIdle");
@@ -189,9 +339,10 @@ impl Drop for VulkanWgpuRenderBackend {
                 }
 
                 self.interface.wait_sync_index();
-                let device = &self.descriptors.device;
-                let device = device.as_hal::<Vulkan, _, _>(|c| c.unwrap().raw_device().clone());
                 device.destroy_image_view(self.backend.target().get_image_view(), None);
+                if let Some(present) = &self.present {
+                    device.destroy_image_view(present.get_image_view(), None);
+                }
                 // Do *not* destroy the VkImage associated with this VkImageView; we didn't create it, wgpu did
             } // Scoped to prevent misuse after being dropped
 
@@ -199,14 +350,7 @@ impl Drop for VulkanWgpuRenderBackend {
             // We created them, but RetroArch took ownership of them,
             // so it's responsible for cleanup.
 
-            global::DEVICE = None;
-            global::INSTANCE = None;
-            global::ENTRY = None;
-
-            #[cfg(debug_assertions)]
-            {
-                global::DEBUG_UTILS = None;
-            }
+            global::reset();
         }
     }
 }
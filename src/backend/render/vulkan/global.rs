@@ -1,5 +1,6 @@
 use ash::vk;
 use ash::extensions::ext;
+use wgpu_hal::api::Vulkan;
 
 // TODO: Should I put these behind locks?
 
@@ -11,5 +12,44 @@ pub(super) static mut INSTANCE: Option<wgpu::Instance> = None;
 // will want to take ownership of it.
 pub(super) static mut DEVICE: Option<ash::Device> = None;
 
+/// The queue family of the queue we gave the frontend in `create_device2`,
+/// or `None` if the frontend created the device itself.
+pub(super) static mut QUEUE_FAMILY_INDEX: Option<u32> = None;
+
+/// Whether to enable the validation layers in release builds too; debug builds always do.
+pub(super) static mut VALIDATION: bool = false;
+
 #[cfg(debug_assertions)]
-pub(super) static mut DEBUG_UTILS: Option<ext::DebugUtils> = None;
\ No newline at end of file
+pub(super) static mut DEBUG_UTILS: Option<ext::DebugUtils> = None;
+
+/// Returns `true` if INSTANCE wraps the given VkInstance,
+/// as opposed to being unset or left over from a context that no longer exists.
+pub(super) unsafe fn instance_matches(raw: vk::Instance) -> bool {
+    INSTANCE
+        .as_ref()
+        .and_then(|instance| instance.as_hal::<Vulkan>())
+        .map_or(false, |instance| instance.shared_instance().raw_instance().handle() == raw)
+}
+
+/// Drops our wrappers around the Vulkan objects created during context negotiation.
+/// Safe to call more than once, and after a partially-failed negotiation.
+///
+/// This never destroys the underlying VkInstance or VkDevice; we created them,
+/// but the frontend took ownership of them and is responsible for destroying them.
+pub(super) unsafe fn reset() {
+    // ash::Device is just a table of function pointers, dropping it doesn't call vkDestroyDevice
+    DEVICE = None;
+    QUEUE_FAMILY_INDEX = None;
+
+    // Also just a function table; the debug messenger (if any) belongs to INSTANCE
+    #[cfg(debug_assertions)]
+    {
+        DEBUG_UTILS = None;
+    }
+
+    // INSTANCE was created without a drop guard, so dropping it only destroys
+    // what wgpu itself created (e.g. its debug messenger), never the VkInstance.
+    // This must happen before the frontend destroys the VkInstance.
+    INSTANCE = None;
+    ENTRY = None;
+}
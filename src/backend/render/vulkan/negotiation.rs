@@ -10,7 +10,8 @@ use rust_libretro::anyhow::{self, anyhow, bail};
 use rust_libretro::contexts::LoadGameContext;
 
 use rust_libretro_sys::{
-    retro_vulkan_context, retro_vulkan_create_device_wrapper_t, retro_vulkan_create_instance_wrapper_t,
+    retro_hw_render_interface_vulkan, retro_vulkan_context, retro_vulkan_create_device_wrapper_t,
+    retro_vulkan_create_instance_wrapper_t,
 };
 use thiserror::Error as ThisError;
 use wgpu_hal::api::Vulkan;
@@ -45,6 +46,8 @@ pub enum VulkanNegotiationError {
 /// (See the git blame for this line for details.)
 const APPLICATION_NAME: &[u8] = b"ruffle_libretro\0";
 
+const VALIDATION_LAYER_NAME: &[u8] = b"VK_LAYER_KHRONOS_validation\0";
+
 unsafe extern "C" fn get_application_info() -> *const ApplicationInfo {
     debug!("get_application_info()");
     #[cfg(feature = "profiler")]
@@ -78,6 +81,12 @@ unsafe extern "C" fn create_instance(
     #[cfg(feature = "profiler")]
     profiling::scope!("retro_hw_render_context_negotiation_interface_vulkan::create_instance");
 
+    if global::INSTANCE.is_some() || global::DEVICE.is_some() {
+        // The frontend didn't call destroy_device last time (e.g. it switched drivers), so don't reuse any of it
+        warn!("Discarding Vulkan handles left over from a previous context");
+        global::reset();
+    }
+
     match create_instance_impl(get_instance_proc_addr, &*app, create_instance_wrapper, opaque) {
         Ok(instance) => {
             assert_ne!(
@@ -120,12 +129,30 @@ unsafe fn create_instance_impl(
     let driver_api_version = entry.try_enumerate_instance_version()?.unwrap_or(vk::API_VERSION_1_0);
     // vkEnumerateInstanceVersion isn't available in Vulkan 1.0
 
-    let flags = if cfg!(debug_assertions) {
+    let flags = if cfg!(debug_assertions) || global::VALIDATION {
         InstanceFlags::VALIDATION | InstanceFlags::DEBUG
     } else {
         InstanceFlags::empty()
     };
 
+    let instance_layers = entry.enumerate_instance_layer_properties()?;
+    let has_layer = |name: &CStr| {
+        instance_layers
+            .iter()
+            .any(|layer| CStr::from_ptr(layer.layer_name.as_ptr()) == name)
+    };
+
+    let validation_layer = CStr::from_bytes_with_nul(VALIDATION_LAYER_NAME)?;
+    let enabled_layers: Vec<*const c_char> = if !flags.contains(InstanceFlags::VALIDATION) {
+        Vec::new()
+    } else if has_layer(validation_layer) {
+        info!("Enabling {validation_layer:?}; its messages will appear in the log");
+        vec![validation_layer.as_ptr()]
+    } else {
+        warn!("Vulkan validation was requested, but {validation_layer:?} isn't installed");
+        Vec::new()
+    };
+
     let required_instance_extensions = VulkanInstance::required_extensions(&entry, driver_api_version, flags)?;
     // This function will strip unsupported instance extensions,
     // so we don't need to check for them ourselves.
@@ -136,7 +163,7 @@ unsafe fn create_instance_impl(
     let instance_create_info = vk::InstanceCreateInfo::builder()
         .application_info(app)
         .enabled_extension_names(&required_instance_extensions_ptr)
-        .enabled_layer_names(&[])
+        .enabled_layer_names(&enabled_layers)
         .build();
 
     let vk_instance = {
@@ -150,13 +177,7 @@ unsafe fn create_instance_impl(
     }
 
     // TODO: Clean up vk_instance if any function beyond here returns an error
-    let has_nv_optimus = {
-        let instance_layers = entry.enumerate_instance_layer_properties()?;
-        let nv_optimus_layer = CStr::from_bytes_with_nul(b"VK_LAYER_NV_optimus\0")?;
-        instance_layers
-            .iter()
-            .any(|layer| CStr::from_ptr(layer.layer_name.as_ptr()) == nv_optimus_layer)
-    };
+    let has_nv_optimus = has_layer(CStr::from_bytes_with_nul(b"VK_LAYER_NV_optimus\0")?);
 
     let ash_instance = {
         #[cfg(feature = "profiler")]
@@ -235,21 +256,24 @@ unsafe fn create_device2_impl(
         None => bail!("Frontend provided a null create_device_wrapper"),
     };
 
+    if !global::instance_matches(instance) {
+        bail!("create_device2 was given VkInstance {instance:?}, which isn't the one made in create_instance");
+    }
+
     let entry = global::ENTRY
         .as_ref()
-        .expect("ENTRY should've been initialized in create_instance");
+        .ok_or_else(|| anyhow!("ENTRY should've been initialized in create_instance"))?;
     let instance = global::INSTANCE
         .as_ref()
-        .expect("INSTANCE should've been initialized in create_instance")
-        .as_hal::<Vulkan>()
-        .expect("INSTANCE should be based on Vulkan")
+        .and_then(|instance| instance.as_hal::<Vulkan>())
+        .ok_or_else(|| anyhow!("INSTANCE should've been initialized in create_instance"))?
         .shared_instance()
         .raw_instance();
 
     #[cfg(debug_assertions)]
     let debug_utils = global::DEBUG_UTILS
         .as_ref()
-        .expect("DEBUG_UTILS should've been initialized in create_instance");
+        .ok_or_else(|| anyhow!("DEBUG_UTILS should've been initialized in create_instance"))?;
 
     match entry.try_enumerate_instance_version() {
         Ok(Some(version)) => {
@@ -416,6 +440,7 @@ unsafe extern "C" fn create_device2(
             context.presentation_queue_family_index = ctx.presentation_queue_family_index;
             context.queue = ctx.queue;
             context.queue_family_index = ctx.queue_family_index;
+            global::QUEUE_FAMILY_INDEX = Some(ctx.queue_family_index);
 
             return true;
         }
@@ -426,6 +451,24 @@ unsafe extern "C" fn create_device2(
     };
 }
 
+/// Called by the frontend before it destroys the VkDevice and VkInstance that it took ownership of,
+/// including when negotiation only partially succeeded.
+unsafe extern "C" fn destroy_device() {
+    debug!("destroy_device()");
+    #[cfg(feature = "profiler")]
+    profiling::scope!("retro_hw_render_context_negotiation_interface_vulkan::destroy_device");
+
+    if let Some(device) = global::DEVICE.as_ref() {
+        // The frontend is about to destroy the device, so make sure it's not still busy with our work
+        if let Err(e) = device.device_wait_idle() {
+            warn!("vkDeviceWaitIdle({:?}) failed with {e}", device.handle());
+        }
+    }
+
+    // Don't call vkDestroyDevice or vkDestroyInstance here, or they'll be destroyed twice
+    global::reset();
+}
+
 // The frontend will request certain extensions and layers for a device which is created.
 // The core must ensure that the queue and queue_family_index support GRAPHICS and COMPUTE.
 fn select_physical_device(instance: &ash::Instance) -> anyhow::Result<vk::PhysicalDevice> {
@@ -615,12 +658,53 @@ fn create_logical_device(
     Ok(create_device_wrapper(&device_create_info))
 }
 
-pub fn enable(ctx: &mut LoadGameContext) -> anyhow::Result<()> {
+/// Wraps the frontend's own VkInstance for wgpu, for when the negotiation interface
+/// wasn't available and so `create_instance` was never called.
+/// The frontend retains ownership of the instance.
+pub(super) unsafe fn adopt_frontend_instance(hw_render: &retro_hw_render_interface_vulkan) -> anyhow::Result<()> {
+    let get_instance_proc_addr = match hw_render.get_instance_proc_addr {
+        Some(p) => p,
+        None => bail!("Frontend provided a null get_instance_proc_addr"),
+    };
+
+    let static_fn = StaticFn { get_instance_proc_addr };
+    let entry = ash::Entry::from_static_fn(static_fn.clone());
+    let driver_api_version = entry.try_enumerate_instance_version()?.unwrap_or(vk::API_VERSION_1_0);
+    let ash_instance = ash::Instance::load(&static_fn, hw_render.instance);
+
+    let instance = VulkanInstance::from_raw(
+        entry.clone(),
+        ash_instance,
+        driver_api_version,
+        get_android_sdk_version()?,
+        Vec::new(),
+        // We don't know which extensions the frontend enabled, so don't claim any
+        InstanceFlags::empty(),
+        false,
+        None,
+    )?;
+
+    global::ENTRY = Some(entry);
+    global::INSTANCE = Some(wgpu::Instance::from_hal::<Vulkan>(instance));
+    info!("Using the frontend's VkInstance {:?} without context negotiation", hw_render.instance);
+
+    Ok(())
+}
+
+/// Forgets the Vulkan handles from the last negotiation, so the next content starts from a clean slate.
+/// Must only be called once nothing uses them anymore, i.e. after the render backend is dropped.
+pub unsafe fn forget_context() {
+    global::reset();
+}
+
+/// `validation` enables the validation layers in release builds, for capturing their output in bug reports.
+pub fn enable(ctx: &mut LoadGameContext, validation: bool) -> anyhow::Result<()> {
     unsafe {
+        global::VALIDATION = validation;
         ctx.enable_hw_render_negotiation_interface_vulkan(
             Some(get_application_info),
             Some(create_device),
-            None,
+            Some(destroy_device),
             Some(create_instance),
             Some(create_device2),
         )?;
@@ -33,7 +33,7 @@ pub struct VulkanRenderInterface {
     gpu: vk::PhysicalDevice,
     device: vk::Device,
     queue: vk::Queue,
-    queue_index: u32,
+    queue_family_index: u32,
     set_image: unsafe extern "C" fn(*mut c_void, *const retro_vulkan_image, u32, *const vk::Semaphore, u32),
     get_sync_index: unsafe extern "C" fn(*mut c_void) -> u32,
     get_sync_index_mask: unsafe extern "C" fn(*mut c_void) -> u32,
@@ -98,7 +98,7 @@ impl VulkanRenderInterface {
             gpu: interface.gpu,
             device: interface.device,
             queue: interface.queue,
-            queue_index: interface.queue_index,
+            queue_family_index: interface.queue_index,
             set_image,
             get_sync_index,
             get_sync_index_mask,
@@ -122,8 +122,10 @@ impl VulkanRenderInterface {
         self.gpu
     }
 
-    pub fn queue_index(&self) -> u32 {
-        self.queue_index
+    /// The family of the frontend's queue. libretro calls this `queue_index`, but RetroArch fills it with
+    /// the queue's family index, and the queue itself is always the first (index 0) in that family.
+    pub fn queue_family_index(&self) -> u32 {
+        self.queue_family_index
     }
 
     pub fn queue(&self) -> vk::Queue {
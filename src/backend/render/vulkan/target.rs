@@ -65,7 +65,10 @@ impl RetroTextureTarget {
             dimension: wgpu::TextureDimension::D2,
             format,
             view_formats: &[format],
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            // COPY_SRC for post-processing
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
         });
 
         unsafe {
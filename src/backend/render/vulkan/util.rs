@@ -13,6 +13,9 @@ use crate::backend::render::wgpu::required_limits;
 
 use super::render_interface::VulkanRenderInterface;
 
+/// The frontend's queue within its family; see [`VulkanRenderInterface::queue_family_index`].
+const QUEUE_INDEX: u32 = 0;
+
 pub type VulkanInstance = <Vulkan as Api>::Instance;
 pub type VulkanDevice = <Vulkan as Api>::Device;
 pub type VulkanPhysicalDevice = <Vulkan as Api>::Adapter;
@@ -170,6 +173,7 @@ pub fn get_android_sdk_version() -> anyhow::Result<u32> {
 pub unsafe fn create_descriptors(
     instance: &wgpu::Instance,
     interface: &VulkanRenderInterface,
+    queue_family_index: u32,
 ) -> anyhow::Result<Descriptors> {
     #[cfg(feature = "profiler")]
     profiling::scope!("util::create_descriptors");
@@ -186,6 +190,21 @@ pub unsafe fn create_descriptors(
             .ok_or(anyhow::anyhow!("Failed to expose physical device {gpu:?}"))?
     };
 
+    // device_from_raw and get_device_queue trust these indexes, so a bad one would read an invalid queue
+    let queue_families = shared_instance
+        .raw_instance()
+        .get_physical_device_queue_family_properties(gpu);
+    match queue_families.get(queue_family_index as usize) {
+        None => anyhow::bail!(
+            "Queue family {queue_family_index} is out of range, physical device {gpu:?} has {} queue families",
+            queue_families.len()
+        ),
+        Some(family) if QUEUE_INDEX >= family.queue_count => {
+            anyhow::bail!("Queue family {queue_family_index} has no queues")
+        }
+        Some(_) => {}
+    }
+
     let open_device = unsafe {
         let device_extensions = adapter.adapter.required_device_extensions(adapter.features);
 
@@ -196,8 +215,8 @@ pub unsafe fn create_descriptors(
             false,
             &device_extensions,
             adapter.features,
-            0,                       // TODO: Add interface.queue_family_index()
-            interface.queue_index(), // wgpu assumes this to be 0
+            queue_family_index,
+            QUEUE_INDEX,
         )?
     };
 
@@ -0,0 +1,142 @@
+use crate::options::ResamplerQuality;
+use std::f64::consts::PI;
+
+/// Input frames on either side of the output position that the sinc kernel reaches.
+const SINC_HALF_WIDTH: usize = 8;
+
+/// Converts a continuous stream of interleaved stereo samples from one rate to another,
+/// taking the input in whatever sized chunks it's mixed in.
+pub struct Resampler {
+    quality: ResamplerQuality,
+    /// Input frames per output frame
+    step: f64,
+    /// Where the next output frame falls, in input frames from the start of `pending`
+    phase: f64,
+    /// Input that hasn't been fully used yet; always holds at least the frames the kernel reaches before `phase`
+    pending: Vec<i16>,
+    /// Scratch space for one output frame's kernel weights
+    weights: Vec<f64>,
+}
+
+impl Resampler {
+    pub fn new(input_rate: u32, output_rate: u32, quality: ResamplerQuality) -> Self {
+        let mut resampler = Self {
+            quality,
+            step: f64::from(input_rate) / f64::from(output_rate.max(1)),
+            phase: 0.0,
+            pending: Vec::new(),
+            weights: Vec::with_capacity(SINC_HALF_WIDTH * 2),
+        };
+        resampler.reset();
+        resampler
+    }
+
+    pub fn quality(&self) -> ResamplerQuality {
+        self.quality
+    }
+
+    /// Whether the rates match, in which case it's cheaper not to use this at all.
+    pub fn is_passthrough(&self) -> bool {
+        self.step == 1.0
+    }
+
+    /// How many more input frames [`Self::push`] needs before [`Self::process`] can make `output_frames`.
+    pub fn input_needed(&self, output_frames: usize) -> usize {
+        let last = self.phase + output_frames.saturating_sub(1) as f64 * self.step;
+        let end = self.phase + output_frames as f64 * self.step;
+        // Interpolating the last frame needs the input frames after it,
+        // and one frame has to be left over to interpolate from next time
+        let needed = (last.floor() as usize + self.lookahead() + 1).max(end.floor() as usize + 1);
+        needed.saturating_sub(self.pending.len() / 2)
+    }
+
+    pub fn push(&mut self, input: &[i16]) {
+        self.pending.extend_from_slice(input);
+    }
+
+    /// Fills `output`, which must not ask for more than [`Self::input_needed`] allowed for.
+    pub fn process(&mut self, output: &mut [i16]) {
+        for (i, frame) in output.chunks_exact_mut(2).enumerate() {
+            let position = self.phase + i as f64 * self.step;
+            let index = position.floor() as usize;
+            let t = position - index as f64;
+            match self.quality {
+                ResamplerQuality::Nearest => {
+                    let nearest = if t < 0.5 { index } else { index + 1 };
+                    frame.copy_from_slice(&self.pending[nearest * 2..nearest * 2 + 2]);
+                }
+                ResamplerQuality::Linear => {
+                    for (channel, sample) in frame.iter_mut().enumerate() {
+                        let a = f64::from(self.pending[index * 2 + channel]);
+                        let b = f64::from(self.pending[(index + 1) * 2 + channel]);
+                        *sample = (a + (b - a) * t).round() as i16;
+                    }
+                }
+                ResamplerQuality::Sinc => {
+                    let first = index + 1 - SINC_HALF_WIDTH;
+                    // Downsampling has to cut off at the output's Nyquist frequency, or high notes alias
+                    let cutoff = self.step.recip().min(1.0);
+                    self.weights.clear();
+                    self.weights.extend((0..SINC_HALF_WIDTH * 2).map(|k| {
+                        let x = position - (first + k) as f64;
+                        Self::sinc(x * cutoff) * Self::sinc(x / SINC_HALF_WIDTH as f64)
+                    }));
+                    // Normalizing keeps a constant input constant, whatever the phase
+                    let total: f64 = self.weights.iter().sum();
+                    for (channel, sample) in frame.iter_mut().enumerate() {
+                        let sum: f64 = self
+                            .weights
+                            .iter()
+                            .enumerate()
+                            .map(|(k, weight)| weight * f64::from(self.pending[(first + k) * 2 + channel]))
+                            .sum();
+                        *sample = (sum / total)
+                            .round()
+                            .clamp(f64::from(i16::MIN), f64::from(i16::MAX)) as i16;
+                    }
+                }
+            }
+        }
+
+        let end = self.phase + (output.len() / 2) as f64 * self.step;
+        let history = self.history();
+        let consumed = (end.floor() as usize)
+            .saturating_sub(history)
+            .min(self.pending.len() / 2 - history - 1);
+        self.pending.drain(..consumed * 2);
+        self.phase = end - consumed as f64;
+    }
+
+    /// Forgets buffered input, e.g. after a stretch of silence.
+    pub fn reset(&mut self) {
+        let history = self.history();
+        self.pending.clear();
+        self.pending.resize((history + 1) * 2, 0);
+        self.phase = history as f64;
+    }
+
+    /// Input frames the kernel reads before the one at or just before an output frame.
+    fn history(&self) -> usize {
+        match self.quality {
+            ResamplerQuality::Nearest | ResamplerQuality::Linear => 0,
+            ResamplerQuality::Sinc => SINC_HALF_WIDTH - 1,
+        }
+    }
+
+    /// Input frames the kernel reads after the one at or just before an output frame.
+    fn lookahead(&self) -> usize {
+        match self.quality {
+            ResamplerQuality::Nearest | ResamplerQuality::Linear => 1,
+            ResamplerQuality::Sinc => SINC_HALF_WIDTH,
+        }
+    }
+
+    /// The normalized sinc function, sin(πx) / πx.
+    fn sinc(x: f64) -> f64 {
+        if x == 0.0 {
+            1.0
+        } else {
+            (PI * x).sin() / (PI * x)
+        }
+    }
+}
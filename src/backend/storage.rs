@@ -28,19 +28,120 @@ pub enum StorageError {
     InterfaceNull,
 }
 
+/// VFS interface versions the core can use, newest first.
+pub const VFS_VERSIONS: [u32; 3] = [3, 2, 1];
+
+/// Copies the functions that exist in the given version of the frontend's VFS interface.
+/// Older frontends' structs end before the newer functions, so those are left as `None`
+/// rather than read from past the end.
+///
+/// # Safety
+/// `iface` must point to a VFS interface of at least `version`.
+pub unsafe fn copy_vfs_interface(iface: *const retro_vfs_interface, version: u32) -> retro_vfs_interface {
+    let functions = match version {
+        1 => 11, // get_path through rename
+        2 => 12, // Adds truncate
+        _ => 19, // Adds stat, mkdir, and the directory functions
+    };
+    let length = (functions * std::mem::size_of::<usize>()).min(std::mem::size_of::<retro_vfs_interface>());
+
+    // Every field is an optional function pointer, so all zeroes is all `None`
+    let mut vfs: retro_vfs_interface = std::mem::zeroed();
+    std::ptr::copy_nonoverlapping(iface as *const u8, &mut vfs as *mut _ as *mut u8, length);
+    vfs
+}
+
+/// Reads a whole file through the frontend's VFS, or returns `None` if it can't.
+pub fn read_file(vfs: &retro_vfs_interface, path: &Path) -> Option<Vec<u8>> {
+    let handle = {
+        let path = CString::new(path.to_str()?).ok()?;
+        let handle = unsafe {
+            vfs.open?(
+                path.as_ptr(),
+                VfsFileOpenFlags::READ.bits(),
+                VfsFileOpenHints::NONE.bits(),
+            )
+        };
+        if handle.is_null() {
+            error!("Failed to open {path:?}");
+            return None;
+        }
+        // Return None if the file doesn't exist or its path is invalid
+        handle
+    };
+
+    let size = unsafe {
+        match vfs.size.map(|size| size(handle)) {
+            None | Some(-1) => {
+                // Error, either vfs.size wasn't provided or it returned -1
+                error!("Failed to get size of {path:?}");
+                vfs.close?(handle);
+                return None;
+                // If vfs.close fails or wasn't provided, not much we can do about it
+            }
+            Some(size) => size,
+        }
+    };
+    // Zeroed rather than just reserved, or the bytes read would sit past the end of the Vec
+    let mut buffer: Vec<u8> = vec![0; size as usize];
+    match vfs
+        .read
+        .map(|read| unsafe { read(handle, buffer.as_mut_ptr() as *mut _, size as u64) })
+    {
+        None | Some(-1) => unsafe {
+            error!("Failed to read from {size}-byte file {path:?}");
+
+            vfs.close?(handle);
+
+            return None;
+            // If vfs.close fails or wasn't provided, not much we can do about it
+        },
+        Some(bytes_read) if bytes_read != size => {
+            warn!("Expected to read {size} bytes from {path:?}, got {bytes_read}");
+            buffer.truncate(bytes_read as usize);
+        }
+        Some(_) => {} // Success, no action needed
+    };
+
+    match vfs.close.map(|close| unsafe { close(handle) }) {
+        Some(0) => {} // Success, no action needed
+        _ => {
+            warn!("Failed to close file handle for {path:?}");
+        }
+    };
+
+    Some(buffer)
+}
+
+/// Directory (within the SharedObjects directory) for objects shared by every movie.
+/// Starts with `#` so it can't collide with a domain name.
+const GLOBAL_DIRECTORY: &str = "#global";
+
 pub struct RetroVfsStorageBackend {
     base_path: PathBuf,
     shared_objects_path: PathBuf,
+    extension: &'static str,
     vfs: Arc<Cell<Option<retro_vfs_interface>>>,
 }
 
 impl RetroVfsStorageBackend {
     pub fn new(base_path: &Path, vfs: Arc<Cell<Option<retro_vfs_interface>>>) -> anyhow::Result<Self> {
-        let shared_objects_path = base_path.join("SharedObjects");
+        Self::with_layout(base_path, "SharedObjects", "sol", vfs)
+    }
+
+    /// Stores each key as `<base_path>/<directory>/<key>.<extension>`.
+    pub fn with_layout(
+        base_path: &Path,
+        directory: &str,
+        extension: &'static str,
+        vfs: Arc<Cell<Option<retro_vfs_interface>>>,
+    ) -> anyhow::Result<Self> {
+        let shared_objects_path = base_path.join(directory);
 
         let result = Self {
             base_path: PathBuf::from(base_path),
             shared_objects_path,
+            extension,
             vfs,
         };
 
@@ -56,7 +157,29 @@ impl RetroVfsStorageBackend {
     }
 
     fn get_shared_object_path(&self, name: &str) -> PathBuf {
-        self.shared_objects_path.join(format!("{name}.sol"))
+        match Self::global_name(name) {
+            Some(global_name) => self
+                .shared_objects_path
+                .join(GLOBAL_DIRECTORY)
+                .join(format!("{global_name}.{}", self.extension)),
+            None => self.get_domain_shared_object_path(name),
+        }
+    }
+
+    fn get_domain_shared_object_path(&self, name: &str) -> PathBuf {
+        self.shared_objects_path.join(format!("{name}.{}", self.extension))
+    }
+
+    /// SharedObjects saved with a local path of "/" (e.g. `localhost//#prefs`) are meant to be visible
+    /// to every movie, so they're stored once regardless of which domain the movie was loaded from.
+    /// Returns the object's name without its domain if `name` is one of these.
+    fn global_name(name: &str) -> Option<&str> {
+        let (_domain, path) = name.split_once('/')?;
+        let path = path.trim_start_matches('/');
+        match path.strip_prefix('#') {
+            Some(global_name) if !global_name.is_empty() && !global_name.contains('/') => Some(global_name),
+            _ => None,
+        }
     }
 
     fn get_back_compat_shared_object_path(&self, name: &str) -> PathBuf {
@@ -66,11 +189,25 @@ impl RetroVfsStorageBackend {
         self.base_path.join(name.replacen("/#", "/", 1))
     }
 
+    fn read(&self, path: &Path) -> Option<Vec<u8>> {
+        read_file(&self.vfs.get()?, path)
+    }
+
     fn ensure_storage_dir(&self, path: &PathBuf) -> anyhow::Result<()> {
         let cpath = CString::new(path.to_str().ok_or(StorageError::InvalidUnicodePath)?)?;
         let vfs = self.vfs.get().ok_or(StorageError::InterfaceNull)?;
 
-        match unsafe { vfs.mkdir.ok_or(StorageError::OperationUnavailable("mkdir"))?(cpath.as_ptr()) } {
+        let mkdir = match vfs.mkdir {
+            Some(mkdir) => mkdir,
+            None => {
+                // VFS v1 and v2 have no mkdir, but the save directory is still a real path
+                std::fs::create_dir_all(path)?;
+                debug!("Created or using existing storage dir {path:?} without the VFS");
+                return Ok(());
+            }
+        };
+
+        match unsafe { mkdir(cpath.as_ptr()) } {
             0 | -2 => {
                 debug!("Created or using existing storage dir {path:?}");
                 Ok(())
@@ -87,63 +224,13 @@ impl StorageBackend for RetroVfsStorageBackend {
             return None;
         }
 
-        let vfs = self.vfs.get()?;
-        let handle = {
-            let path = CString::new(path.to_str()?).ok()?;
-            let handle = unsafe {
-                vfs.open?(
-                    path.as_ptr(),
-                    VfsFileOpenFlags::READ.bits(),
-                    VfsFileOpenHints::NONE.bits(),
-                )
-            };
-            if handle.is_null() {
-                error!("Failed to open {path:?}");
-                return None;
-            }
-            // Return None if the file doesn't exist or its path is invalid
-            handle
-        };
-
-        let size = unsafe {
-            match vfs.size.map(|size| size(handle)) {
-                None | Some(-1) => {
-                    // Error, either vfs.size wasn't provided or it returned -1
-                    error!("Failed to get size of {path:?}");
-                    vfs.close?(handle);
-                    return None;
-                    // If vfs.close fails or wasn't provided, not much we can do about it
-                }
-                Some(size) => size,
-            }
-        };
-        let mut buffer: Vec<u8> = Vec::with_capacity(size as usize);
-        match vfs
-            .read
-            .map(|read| unsafe { read(handle, buffer.as_mut_ptr() as *mut _, size as u64) })
-        {
-            None | Some(-1) => unsafe {
-                error!("Failed to read from {size}-byte file {path:?}");
-
-                vfs.close?(handle);
-
-                return None;
-                // If vfs.close fails or wasn't provided, not much we can do about it
-            },
-            Some(bytes_read) if bytes_read != size => {
-                warn!("Expected to read {size} bytes from {path:?}, got {bytes_read}");
+        match self.read(&path) {
+            None if Self::global_name(name).is_some() => {
+                // Global objects used to be stored per-domain
+                self.read(&self.get_domain_shared_object_path(name))
             }
-            Some(_) => {} // Success, no action needed
-        };
-
-        match vfs.close.map(|close| unsafe { close(handle) }) {
-            Some(0) => {} // Success, no action needed
-            _ => {
-                warn!("Failed to close file handle for {path:?}");
-            }
-        };
-
-        Some(buffer)
+            result => result,
+        }
     }
 
     fn put(&mut self, name: &str, value: &[u8]) -> bool {
@@ -218,6 +305,11 @@ impl StorageBackend for RetroVfsStorageBackend {
 
     fn get_size(&self, name: &str) -> Option<usize> {
         let vfs = self.vfs.get()?;
+        if vfs.stat.is_none() {
+            // VFS v1 and v2 have no stat, so read the whole thing instead
+            return self.get(name).map(|data| data.len());
+        }
+
         let name = CString::new(name).ok()?;
         let size = 0i32;
 
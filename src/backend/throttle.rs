@@ -0,0 +1,70 @@
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+/// Limits how many network requests can be in flight at once.
+/// Requests past the limit wait until one of the others finishes.
+pub struct RequestThrottle {
+    /// `None` for no limit
+    limit: Option<usize>,
+    active: Cell<usize>,
+    waiting: RefCell<VecDeque<Waker>>,
+}
+
+impl RequestThrottle {
+    pub fn new(limit: Option<usize>) -> Rc<Self> {
+        Rc::new(Self {
+            limit: limit.map(|limit| limit.max(1)),
+            active: Cell::new(0),
+            waiting: RefCell::new(VecDeque::new()),
+        })
+    }
+
+    /// Waits for a free slot; the request keeps it until the returned [`RequestPermit`] is dropped.
+    pub fn acquire(self: &Rc<Self>) -> Acquire {
+        Acquire {
+            throttle: self.clone(),
+        }
+    }
+
+    fn has_room(&self) -> bool {
+        self.limit.map_or(true, |limit| self.active.get() < limit)
+    }
+}
+
+pub struct Acquire {
+    throttle: Rc<RequestThrottle>,
+}
+
+impl Future for Acquire {
+    type Output = RequestPermit;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let throttle = &self.throttle;
+        if throttle.has_room() {
+            throttle.active.set(throttle.active.get() + 1);
+            Poll::Ready(RequestPermit {
+                throttle: throttle.clone(),
+            })
+        } else {
+            throttle.waiting.borrow_mut().push_back(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+pub struct RequestPermit {
+    throttle: Rc<RequestThrottle>,
+}
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        self.throttle.active.set(self.throttle.active.get() - 1);
+        if let Some(waker) = self.throttle.waiting.borrow_mut().pop_front() {
+            waker.wake();
+        }
+    }
+}
@@ -1,34 +1,61 @@
 use arboard::Clipboard;
-use log::{error, warn, info};
-use ruffle_core::backend::ui::{FullscreenError, MouseCursor, UiBackend};
+use log::{debug, error, warn, info};
+use ruffle_core::backend::ui::{FullscreenError, LanguageIdentifier, MouseCursor, UiBackend};
 use rust_libretro::environment;
-use rust_libretro::sys::{retro_environment_t, retro_log_level, retro_message_target, retro_message_type};
-use rust_libretro::types::MessageProgress;
+use rust_libretro::sys::{retro_environment_t, retro_log_level};
 use std::cell::Cell;
 use std::sync::Arc;
 
+use crate::util::{self, language};
+
 const UNSUPPORTED_CONTENT_MESSAGE: &str = "\
 This content requires ActionScript 3, which Ruffle doesn't support yet.
 Interactivity will be missing or limited.";
 
-const DOWNLOAD_FAILED_MESSAGE: &str = "Ruffle failed to open or download this file.";
+pub(crate) const DOWNLOAD_FAILED_MESSAGE: &str = "Ruffle failed to open or download this file.";
 
 pub struct RetroUiBackend {
-    clipboard: Clipboard,
+    /// `None` if clipboard access is disabled or unavailable
+    clipboard: Option<Clipboard>,
     cursor_visible: bool,
     cursor: MouseCursor,
     environment: Arc<Cell<retro_environment_t>>,
+    language: LanguageIdentifier,
 }
 
 impl RetroUiBackend {
-    pub fn new(environment: Arc<Cell<retro_environment_t>>) -> Self {
+    pub fn new(environment: Arc<Cell<retro_environment_t>>, clipboard_enabled: bool) -> Self {
+        let language = match unsafe { environment::get_language(environment.get()) } {
+            Ok(language) => language::to_language_identifier(language),
+            Err(e) => {
+                warn!("RETRO_ENVIRONMENT_GET_LANGUAGE failed, defaulting to {}: {e}", language::DEFAULT_LOCALE);
+                language::DEFAULT_LOCALE.parse().unwrap()
+            }
+        };
+        info!("Reporting language {language} to content");
+
+        let clipboard = if clipboard_enabled {
+            Clipboard::new()
+                .map_err(|e| warn!("Clipboard is unavailable, copy and paste won't work: {e}"))
+                .ok()
+        } else {
+            info!("Clipboard access is disabled");
+            None
+        };
+
         Self {
-            clipboard: Clipboard::new().unwrap(),
+            clipboard,
             cursor_visible: true,
             cursor: MouseCursor::Arrow,
             environment,
+            language,
         }
     }
+
+    /// The cursor the content wants shown, or `None` if it hid the cursor.
+    pub fn cursor(&self) -> Option<MouseCursor> {
+        self.cursor_visible.then_some(self.cursor)
+    }
 }
 
 impl UiBackend for RetroUiBackend {
@@ -36,85 +63,90 @@ impl UiBackend for RetroUiBackend {
         self.cursor_visible
     }
 
+    // libretro has no API for changing the frontend's cursor shape or visibility,
+    // so these are recorded for the cursor the core draws itself (see `Ruffle::show_cursor`).
     fn set_mouse_visible(&mut self, visible: bool) {
+        if visible != self.cursor_visible {
+            debug!("[ruffle] Content {} the mouse cursor", if visible { "showed" } else { "hid" });
+        }
         self.cursor_visible = visible;
     }
 
     fn set_mouse_cursor(&mut self, cursor: MouseCursor) {
+        if cursor != self.cursor {
+            debug!("[ruffle] Content changed the mouse cursor to {cursor:?}");
+        }
         self.cursor = cursor;
     }
 
     fn clipboard_content(&mut self) -> String {
-        todo!()
+        match self.clipboard.as_mut().map(|clipboard| clipboard.get_text()) {
+            Some(Ok(content)) => content,
+            Some(Err(error)) => {
+                error!("[ruffle] Failed to get clipboard content: {error}");
+                String::new()
+            }
+            None => String::new(),
+        }
     }
 
     fn set_clipboard_content(&mut self, content: String) {
-        if let Err(error) = self.clipboard.set_text(content) {
-            error!("[ruffle] Failed to set clipboard content: {error}");
+        if let Some(clipboard) = &mut self.clipboard {
+            if let Err(error) = clipboard.set_text(content) {
+                error!("[ruffle] Failed to set clipboard content: {error}");
+            }
         }
     }
 
-    fn set_fullscreen(&mut self, _is_full: bool) -> Result<(), FullscreenError> {
-        todo!()
+    fn set_fullscreen(&mut self, is_full: bool) -> Result<(), FullscreenError> {
+        // The frontend owns the window, so there's nothing to resize;
+        // the stage tracks whether it's in (interactive) fullscreen by itself.
+        debug!("[ruffle] set_fullscreen({is_full})");
+        Ok(())
     }
 
     fn display_unsupported_message(&self) {
         let result = unsafe {
-            environment::set_message_ext(
+            util::message::set_message(
                 self.environment.get(),
                 UNSUPPORTED_CONTENT_MESSAGE,
                 3000,
                 0,
                 retro_log_level::RETRO_LOG_WARN,
-                retro_message_target::RETRO_MESSAGE_TARGET_ALL,
-                retro_message_type::RETRO_MESSAGE_TYPE_NOTIFICATION,
-                MessageProgress::Indeterminate,
             )
         };
 
         if let Err(e) = result {
             warn!("{}", UNSUPPORTED_CONTENT_MESSAGE);
-            warn!("RETRO_ENVIRONMENT_SET_MESSAGE_EXT failed: {e}");
+            warn!("Failed to show message: {e}");
         }
     }
 
     fn display_root_movie_download_failed_message(&self) {
         let result = unsafe {
-            environment::set_message_ext(
+            util::message::set_message(
                 self.environment.get(),
                 DOWNLOAD_FAILED_MESSAGE,
                 3000,
                 0,
                 retro_log_level::RETRO_LOG_WARN,
-                retro_message_target::RETRO_MESSAGE_TARGET_ALL,
-                retro_message_type::RETRO_MESSAGE_TYPE_NOTIFICATION,
-                MessageProgress::Indeterminate,
             )
         };
 
         if let Err(e) = result {
             warn!("{}", DOWNLOAD_FAILED_MESSAGE);
-            warn!("RETRO_ENVIRONMENT_SET_MESSAGE_EXT failed: {e}");
+            warn!("Failed to show message: {e}");
         }
     }
 
     fn message(&self, message: &str) {
         let result = unsafe {
-            environment::set_message_ext(
-                self.environment.get(),
-                message,
-                1000,
-                0,
-                retro_log_level::RETRO_LOG_INFO,
-                retro_message_target::RETRO_MESSAGE_TARGET_ALL,
-                retro_message_type::RETRO_MESSAGE_TYPE_NOTIFICATION,
-                MessageProgress::Indeterminate,
-            )
+            util::message::set_message(self.environment.get(), message, 1000, 0, retro_log_level::RETRO_LOG_INFO)
         };
 
         if let Err(e) = result {
             info!("{}", message);
-            warn!("RETRO_ENVIRONMENT_SET_MESSAGE_EXT failed: {e}");
+            warn!("Failed to show message: {e}");
         }
     }
 
@@ -122,7 +154,7 @@ impl UiBackend for RetroUiBackend {
         todo!("Open RetroArch's virtual keyboard");
     }
 
-    fn language(&self) -> &ruffle_core::backend::ui::LanguageIdentifier {
-        todo!()
+    fn language(&self) -> &LanguageIdentifier {
+        &self.language
     }
 }
@@ -9,9 +9,17 @@ use rust_libretro::contexts::GenericContext;
 use rust_libretro::sys::retro_system_av_info;
 use rust_libretro::{contexts::*, proc::CoreOptions, sys::*};
 
+use crate::backend::navigator::RetroExecutor;
+use crate::core::adaptive::AdaptiveQuality;
+use crate::core::benchmark::Benchmark;
 use crate::core::config::Config;
+use crate::core::ending::EndWatcher;
+use crate::core::limiter::FrameLimiter;
+use crate::core::pacing::FramePacer;
+use crate::core::repeat::KeyRepeat;
 use crate::core::state::PlayerState;
 use crate::util::mouse::MouseState;
+use crate::util::wav::WavWriter;
 
 #[derive(CoreOptions)]
 #[categories(
@@ -29,9 +37,31 @@ use crate::util::mouse::MouseState;
     "content_settings",
     "Content",
     "Options related to content."
+},
+{
+    "input_settings",
+    "Input",
+    "Options related to input."
+},
+{
+    "developer_settings",
+    "Developer",
+    "Options for diagnosing and tuning the core. Most users should leave these alone."
 }
 )]
 #[options(
+{
+    "ruffle_use_defaults",
+    "Content > Use Default Settings",
+    "Use Default Settings",
+    "Ignores every other option here and uses its default value instead, applied immediately. Your own settings aren't lost; turn this off to go back to them.",
+    "",
+    "content_settings",
+    {
+        { "false" },
+        { "true" },
+    }
+},
 {
     "ruffle_autoplay",
     "Video > Autoplay",
@@ -44,6 +74,38 @@ use crate::util::mouse::MouseState;
         { "false" },
     }
 },
+{
+    "ruffle_paused_frame",
+    "Video > Frame Shown Before Playing",
+    "Frame Shown Before Playing",
+    "With autoplay off, shows this frame instead of the first, which is often a blank preloader. Playback still starts from the beginning. Frames that haven't loaded yet can't be shown.",
+    "",
+    "video_settings",
+    {
+        { "1", "First" },
+        { "2", "2nd" },
+        { "5", "5th" },
+        { "10", "10th" },
+        { "30", "30th" },
+        { "last", "Last" },
+    },
+    "1"
+},
+{
+    "ruffle_end_behavior",
+    "Video > When the Movie Ends",
+    "When the Movie Ends",
+    "What to do once a movie with more than one frame reaches its last frame. Games that stop on their last frame count as finished too, so 'Close Core' is best kept for animations. 'Stop on Last Frame' keeps the movie interactive, so end screens' buttons still work.",
+    "",
+    "video_settings",
+    {
+        { "loop", "Follow the Movie" },
+        { "freeze", "Stop on Last Frame" },
+        { "restart", "Restart" },
+        { "shutdown", "Close Core" },
+    },
+    "loop"
+},
 {
     "ruffle_letterbox",
     "Video > Letterbox",
@@ -59,6 +121,54 @@ When letterboxed, black bars will be rendered around the exterior margins of the
     },
     "fullscreen"
 },
+{
+    "ruffle_post_process",
+    "Video > Post-Processing",
+    "Post-Processing",
+    "Applies an effect to the movie's output before it's sent to the frontend. Only available with the OpenGL and Vulkan renderers.",
+    "",
+    "video_settings",
+    {
+        { "off", "Off" },
+        { "scanlines", "Scanlines" },
+        { "crt", "CRT" },
+    }
+},
+{
+    "ruffle_overlay",
+    "Video > Guide Overlay",
+    "Guide Overlay",
+    "Draws layout guides over the stage, for checking what a TV might crop. Only available with the OpenGL and Vulkan renderers.",
+    "",
+    "video_settings",
+    {
+        { "off", "Off" },
+        { "safe_area", "Safe Area" },
+        { "thirds", "Thirds" },
+        { "grid", "Grid" },
+    },
+    "off"
+},
+{
+    "ruffle_stage_align",
+    "Video > Stage Alignment",
+    "Stage Alignment",
+    "Positions the content within the letterboxed area. \"Movie Default\" lets the movie decide. Takes effect when content is loaded.",
+    "",
+    "video_settings",
+    {
+        { "movie", "Movie Default" },
+        { "center", "Center" },
+        { "top_left", "Top Left" },
+        { "top", "Top" },
+        { "top_right", "Top Right" },
+        { "left", "Left" },
+        { "right", "Right" },
+        { "bottom_left", "Bottom Left" },
+        { "bottom", "Bottom" },
+        { "bottom_right", "Bottom Right" },
+    }
+},
 {
     "ruffle_max_execution_duration",
     "Content > Max Execution Duration",
@@ -90,6 +200,104 @@ When letterboxed, black bars will be rendered around the exterior margins of the
         { "4", "4x" },
     },
 },
+{
+    "ruffle_adaptive_quality",
+    "Video > Adaptive Quality",
+    "Adaptive Quality",
+    "Lowers the movie's rendering quality when the core can't keep up (detected by the frontend's audio buffer running low), and restores it once performance recovers.",
+    "",
+    "video_settings",
+    {
+        { "false" },
+        { "true" },
+    }
+},
+{
+    "ruffle_max_fps",
+    "Developer > Max Tick Rate",
+    "Max Tick Rate",
+    "Limits how many times per second the movie is advanced, and keeps its timeline from running ahead of real time, however often the frontend runs the core.",
+    "",
+    "developer_settings",
+    {
+        { "off", "Off" },
+        { "30", "30 per second" },
+        { "60", "60 per second" },
+        { "120", "120 per second" },
+    },
+    "off"
+},
+{
+    "ruffle_frame_pacing",
+    "Video > Frame Pacing",
+    "Frame Pacing",
+    "Evens out small variations in the frontend's frame timing, which can reduce judder in steady animations. Takes effect when content is loaded.",
+    "",
+    "video_settings",
+    {
+        { "false" },
+        { "true" },
+    }
+},
+{
+    "ruffle_text_scale",
+    "Video > Text Scale",
+    "Text Scale",
+    "Renders the stage at a multiple of its native resolution, then scales each frame back down before the frontend gets it. Makes small text and thin lines smoother at the cost of GPU time. Takes effect when content is loaded.",
+    "",
+    "video_settings",
+    {
+        { "1", "1x" },
+        { "2", "2x" },
+        { "3", "3x" },
+        { "4", "4x" },
+    },
+    "1"
+},
+{
+    "ruffle_letterbox_color",
+    "Video > Letterbox Color",
+    "Letterbox Color",
+    "Colors the bars around a letterboxed movie. \"Stage Color\" matches the movie's background.",
+    "",
+    "video_settings",
+    {
+        { "black", "Black" },
+        { "stage", "Stage Color" },
+        { "#ffffff", "White" },
+        { "#808080", "Gray" },
+        { "#202020", "Dark Gray" },
+    },
+    "black"
+},
+{
+    "ruffle_aspect_ratio_mode",
+    "Video > Aspect Ratio Handling",
+    "Aspect Ratio Handling",
+    "How the movie's aspect ratio is preserved. \"Frontend Geometry\" tells the frontend the movie's aspect ratio and lets it scale the picture. The padding choices instead render a texture of that aspect ratio with the movie centered inside, for frontends that stretch the picture to fit. Takes effect when content is loaded.",
+    "",
+    "video_settings",
+    {
+        { "geometry", "Frontend Geometry" },
+        { "pad_4_3", "Pad Texture to 4:3" },
+        { "pad_16_9", "Pad Texture to 16:9" },
+    },
+    "geometry"
+},
+{
+    "ruffle_render_scale",
+    "Video > Render Scale",
+    "Render Scale",
+    "Renders the stage below its native resolution and lets the frontend scale it up. Helps large movies run on weak GPUs at the cost of sharpness. Combines with Text Scale. Takes effect when content is loaded.",
+    "",
+    "video_settings",
+    {
+        { "1.0", "1x" },
+        { "0.75", "0.75x" },
+        { "0.5", "0.5x" },
+    },
+    "1.0"
+},
 {
     "ruffle_warn_on_unsupported_content",
     "Content > Warn on Unsupported Content",
@@ -102,24 +310,259 @@ When letterboxed, black bars will be rendered around the exterior margins of the
         { "false" },
     }
 },
+{
+    "ruffle_fallback_backend",
+    "Video > Fallback Renderer Backend",
+    "Fallback Renderer Backend",
+    "Selects the graphics API used when the frontend can't provide a hardware context and Ruffle renders offscreen instead. Falls back to 'Auto' if the chosen API isn't available. Takes effect when content is loaded.",
+    "",
+    "video_settings",
+    {
+        { "auto", "Auto" },
+        { "gl", "OpenGL" },
+        { "vulkan", "Vulkan" },
+    },
+    "auto"
+},
+{
+    "ruffle_show_render_status",
+    "Video > Show Renderer Status",
+    "Show Renderer Status",
+    "Shows which renderer is active, its internal resolution, and its MSAA level. The status is always shown once when content loads; enable this to show it again.",
+    "",
+    "video_settings",
+    {
+        { "false" },
+        { "true" },
+    }
+},
+{
+    "ruffle_max_bitmap_memory",
+    "Video > Bitmap Memory Warning",
+    "Bitmap Memory Warning",
+    "Logs a warning once bitmaps use more than this much GPU memory, to help tell when content that loads too many images is about to run out. Bitmaps are still loaded past the limit.",
+    "",
+    "video_settings",
+    {
+        { "off", "No Limit" },
+        { "256", "256 MB" },
+        { "512", "512 MB" },
+        { "1024", "1 GB" },
+        { "2048", "2 GB" },
+    },
+    "off"
+},
+{
+    "ruffle_vulkan_frames",
+    "Video > Vulkan Frame Queue Depth",
+    "Vulkan Frame Queue Depth",
+    "Limits how many frames can be queued on the GPU when using Vulkan. Lower values reduce input latency, higher values reduce stutter. 'Auto' lets the frontend decide.",
+    "",
+    "video_settings",
+    {
+        { "auto", "Auto" },
+        { "1" },
+        { "2" },
+        { "3" },
+    },
+    "auto"
+},
+{
+    "ruffle_power_save",
+    "Content > Power Save",
+    "Power Save",
+    "Sleeps for part of each frame when the movie has nothing new to draw and no input was received. Reduces CPU usage for idle or looping content.",
+    "",
+    "content_settings",
+    {
+        { "false" },
+        { "true" },
+    }
+},
+{
+    "ruffle_auto_device",
+    "Input > Detect Input Device",
+    "Detect Input Device",
+    "Until a device is chosen for port 1, guesses from the movie's code whether it's played with the keyboard or the mouse, and shows that device's controls in the frontend. Both still work until a device is chosen. Choosing a device in the frontend always takes precedence. Takes effect when content is loaded.",
+    "",
+    "input_settings",
+    {
+        { "true" },
+        { "false" },
+    },
+    "true"
+},
+{
+    "ruffle_keyboard_passthrough",
+    "Input > Keys Sent to Content",
+    "Keys Sent to Content",
+    "Keeps some keys from reaching the movie, so pressing a frontend hotkey doesn't also trigger something in the game. The frontend still sees every key.",
+    "",
+    "input_settings",
+    {
+        { "all", "All Keys" },
+        { "reserve_menu", "All but Escape and F1" },
+        { "reserve_function_keys", "All but Escape and F1-F12" },
+    },
+    "all"
+},
+{
+    "ruffle_pause_key",
+    "Input > Pause Key Pauses Movie",
+    "Pause Key Pauses Movie",
+    "Lets the keyboard's Pause key pause and resume the movie's timeline. While enabled, the movie never sees the Pause key itself. Input is still processed while paused.",
+    "",
+    "input_settings",
+    {
+        { "true" },
+        { "false" },
+    }
+},
+{
+    "ruffle_key_repeat_delay",
+    "Input > Key Repeat Delay",
+    "Key Repeat Delay",
+    "How long a key has to be held before it starts repeating, for menus that expect held keys to repeat. Leave off if the frontend already repeats keys.",
+    "",
+    "input_settings",
+    {
+        { "off", "Off" },
+        { "250", "250 ms" },
+        { "500", "500 ms" },
+        { "750", "750 ms" },
+    },
+    "off"
+},
+{
+    "ruffle_key_repeat_rate",
+    "Input > Key Repeat Rate",
+    "Key Repeat Rate",
+    "How often a held key repeats once it starts.",
+    "",
+    "input_settings",
+    {
+        { "10", "10 per second" },
+        { "20", "20 per second" },
+        { "30", "30 per second" },
+    },
+    "30"
+},
+{
+    "ruffle_mouse_confine",
+    "Input > Confine Mouse to Stage",
+    "Confine Mouse to Stage",
+    "Keeps the mouse cursor within the movie's stage. When disabled, the cursor can leave the stage and the movie is told it left, which some content relies on for hover effects.",
+    "",
+    "input_settings",
+    {
+        { "true" },
+        { "false" },
+    }
+},
+{
+    "ruffle_software_cursor",
+    "Input > Draw Mouse Cursor",
+    "Draw Mouse Cursor",
+    "Draws the mouse cursor over the movie, in the shape the movie asks for (e.g. a hand over buttons), and hides it when the movie does. Useful with the analog stick or when the frontend hides its own cursor. Not shown by the fallback renderer.",
+    "",
+    "input_settings",
+    {
+        { "false" },
+        { "true" },
+    }
+},
+{
+    "ruffle_analog_cursor",
+    "Input > Analog Stick Cursor",
+    "Analog Stick Cursor",
+    "Lets the left analog stick move the mouse cursor. \"Accelerated\" keeps small tilts slow for precise clicking while full tilt still crosses the stage quickly.",
+    "",
+    "input_settings",
+    {
+        { "linear", "Linear" },
+        { "accelerated", "Accelerated" },
+        { "off", "Off" },
+    },
+    "linear"
+},
+{
+    "ruffle_mouse_sensitivity",
+    "Input > Mouse Sensitivity",
+    "Mouse Sensitivity",
+    "Scales how far the cursor moves for a given mouse movement.",
+    "",
+    "input_settings",
+    {
+        { "1.0", "100%" },
+        { "0.25", "25%" },
+        { "0.5", "50%" },
+        { "0.75", "75%" },
+        { "1.25", "125%" },
+        { "1.5", "150%" },
+        { "2.0", "200%" },
+        { "3.0", "300%" },
+    }
+},
+{
+    "ruffle_mouse_axis",
+    "Input > Mouse Axes",
+    "Mouse Axes",
+    "Swaps or inverts the mouse's movement, for frontends or devices that report it differently. Try this if the cursor moves the wrong way.",
+    "",
+    "input_settings",
+    {
+        { "normal", "Normal" },
+        { "swap", "Swap X and Y" },
+        { "invert_x", "Invert X" },
+        { "invert_y", "Invert Y" },
+        { "invert_both", "Invert X and Y" },
+    },
+    "normal"
+},
+{
+    "ruffle_wheel_invert",
+    "Input > Invert Mouse Wheel",
+    "Invert Mouse Wheel",
+    "Reverses the direction the mouse wheel scrolls, for those who prefer \"natural\" scrolling.",
+    "",
+    "input_settings",
+    {
+        { "false" },
+        { "true" },
+    },
+    "false"
+},
+{
+    "ruffle_right_click",
+    "Input > Right Click",
+    "Right Click",
+    "Choose whether right-clicking opens the movie's context menu, as in Flash Player, or is passed to the movie as input. Games that use the right mouse button need \"Input\".",
+    "",
+    "input_settings",
+    {
+        { "context-menu", "Context Menu" },
+        { "input", "Input" },
+    }
+},
 {
     "ruffle_file_access_policy",
     "Content > file:// Protocol Policy",
     "file:// Protocol Policy",
-    "Decide what to do if the movie requests a file on the local file system with file:// URLs. Make sure you trust this movie!",
+    "Decide what to do if the movie requests a file on the local file system with file:// URLs. Files in the movie's own folder, such as the rest of a movie that's split into several files, are always allowed. Make sure you trust this movie! Takes effect when content is loaded.",
     "",
     "content_settings",
     {
         { "never", "Never" },
         { "notify", "Notify of Access" },
         { "always", "Always" },
-    }
+    },
+    "never"
 },
 {
     "ruffle_web_browser_access",
     "Content > Web Browser Access",
     "Web Browser Access",
-    "Decide what to do if the movie navigates the browser to a URL.",
+    "Decide what to do if the movie navigates the browser to a URL. Takes effect when content is loaded.",
     "",
     "content_settings",
     {
@@ -128,6 +571,99 @@ When letterboxed, black bars will be rendered around the exterior margins of the
         { "external", "External Window" },
     }
 },
+{
+    "ruffle_external_interface",
+    "Content > ExternalInterface",
+    "ExternalInterface",
+    "Decide what to do when the movie calls out to its host page with ExternalInterface. There's no page to call, so calls can be logged, or also mapped to core actions (e.g. 'exit' closes the core).",
+    "",
+    "content_settings",
+    {
+        { "log", "Log Calls" },
+        { "actions", "Log Calls and Map to Core Actions" },
+        { "off", "Unavailable" },
+    },
+    "log"
+},
+{
+    "ruffle_clipboard",
+    "Content > Clipboard Access",
+    "Clipboard Access",
+    "Lets the movie read from and write to the system clipboard. When disabled, the core never touches the clipboard. Takes effect when content is loaded.",
+    "",
+    "content_settings",
+    {
+        { "true" },
+        { "false" },
+    }
+},
+{
+    "ruffle_allow_quit",
+    "Content > Allow Content to Quit",
+    "Allow Content to Quit",
+    "Lets the movie close the core with fscommand(\"quit\"), e.g. from a Quit button. Disabled by default so content can't close the core unexpectedly.",
+    "",
+    "content_settings",
+    {
+        { "false" },
+        { "true" },
+    }
+},
+{
+    "ruffle_network_access",
+    "Content > Network Access",
+    "Network Access",
+    "Lets the movie download data, images, and other movies over HTTP(S). Local files and loading remote content from the frontend aren't affected. Takes effect when content is loaded.",
+    "",
+    "content_settings",
+    {
+        { "false" },
+        { "true" },
+    },
+    "false"
+},
+{
+    "ruffle_upgrade_to_https",
+    "Content > Upgrade to HTTPS",
+    "Upgrade to HTTPS",
+    "Requests http:// URLs over https:// instead, since many servers no longer answer plain HTTP. Other schemes are left alone. Takes effect when content is loaded.",
+    "",
+    "content_settings",
+    {
+        { "true" },
+        { "false" },
+    },
+    "true"
+},
+{
+    "ruffle_http_cache",
+    "Content > HTTP Cache",
+    "HTTP Cache",
+    "Keeps files the movie downloads in the save directory so they don't have to be downloaded again. Honors the server's Cache-Control header. Takes effect when content is loaded.",
+    "",
+    "content_settings",
+    {
+        { "true" },
+        { "false" },
+    }
+},
+{
+    "ruffle_max_requests",
+    "Content > Simultaneous Downloads",
+    "Simultaneous Downloads",
+    "Limits how many files the movie can download at once. Further requests wait their turn, so content that requests dozens of assets at once doesn't exhaust the network or memory. Takes effect when content is loaded.",
+    "",
+    "content_settings",
+    {
+        { "2" },
+        { "4" },
+        { "6" },
+        { "8" },
+        { "16" },
+        { "unlimited", "Unlimited" },
+    },
+    "6"
+},
 {
     "ruffle_spoofed_url",
     "Content > Spoofed URL",
@@ -147,26 +683,176 @@ When letterboxed, black bars will be rendered around the exterior margins of the
     "ruffle_load_behavior",
     "Content > Load Behavior",
     "Load Behavior",
-    "Configures how the root movie should be loaded.",
-    "Configures how the root movie should be loaded.",
+    "Configures how the root movie should be loaded. Streaming parses the movie a little at a time and starts playing as soon as the first frame is ready, so big movies show their preloader quickly. Blocking parses the whole movie before the first frame. Delayed parses it in the background, but waits for all of it before playing. Takes effect when content is loaded.",
+    "",
     "content_settings",
     {
-        { "streaming" },
-        { "blocking" },
-        { "delayed" },
-    }
+        { "streaming", "Streaming" },
+        { "blocking", "Blocking" },
+        { "delayed", "Delayed" },
+    },
+    "streaming"
 },
 {
     "ruffle_audio_sample_rate",
     "Audio > Sample Rate",
     "Sample Rate",
-    "Configures how the root movie should be loaded.",
-    "Configures how the root movie should be loaded.",
+    "The rate audio is sent to the frontend at. The frontend resamples it to whatever its audio driver uses, so this rarely needs changing. Changing it after content is loaded resamples the mixer's output.",
+    "",
     "audio_settings",
     {
         { "44100" },
         { "48000" },
     }
+},
+{
+    "ruffle_audio_volume",
+    "Audio > Volume",
+    "Volume",
+    "The movie's master volume.",
+    "",
+    "audio_settings",
+    {
+        { "0", "0%" },
+        { "10", "10%" },
+        { "20", "20%" },
+        { "30", "30%" },
+        { "40", "40%" },
+        { "50", "50%" },
+        { "60", "60%" },
+        { "70", "70%" },
+        { "80", "80%" },
+        { "90", "90%" },
+        { "100", "100%" },
+    },
+    "100"
+},
+{
+    "ruffle_audio_mute",
+    "Audio > Mute",
+    "Mute",
+    "Silences the movie without pausing it; sounds keep playing in the background, so they're where they should be when unmuted.",
+    "",
+    "audio_settings",
+    {
+        { "false" },
+        { "true" },
+    },
+    "false"
+},
+{
+    "ruffle_audio_channels",
+    "Audio > Channels",
+    "Channels",
+    "Mono mixes both channels together and plays the result on both speakers, for single-speaker setups or movies with hard-panned sound.",
+    "",
+    "audio_settings",
+    {
+        { "stereo", "Stereo" },
+        { "mono", "Mono" },
+    },
+    "stereo"
+},
+{
+    "ruffle_resampler_quality",
+    "Audio > Resampler Quality",
+    "Resampler Quality",
+    "Selects how audio is interpolated when the output sample rate differs from the rate the movie was loaded with. Sinc sounds cleanest but costs the most CPU; Nearest is the cheapest but adds audible distortion.",
+    "",
+    "audio_settings",
+    {
+        { "nearest", "Nearest" },
+        { "linear", "Linear" },
+        { "sinc", "Sinc" },
+    },
+    "linear"
+},
+{
+    "ruffle_audio_precision",
+    "Audio > Precision",
+    "Precision",
+    "Selects how audio is mixed. Float mixes in 32-bit floating point and converts to the frontend's 16-bit format only at the very end (with dithering), preserving headroom for loud or heavily layered sounds. Takes effect when content is loaded.",
+    "",
+    "audio_settings",
+    {
+        { "int16", "16-bit Integer" },
+        { "float", "32-bit Float" },
+    }
+},
+{
+    "ruffle_benchmark",
+    "Developer > Benchmark",
+    "Benchmark",
+    "Times the player over the given number of frames after content loads, then shows the average tick and render times. Takes effect when content is loaded.",
+    "",
+    "developer_settings",
+    {
+        { "off", "Off" },
+        { "300", "300 Frames" },
+        { "1000", "1000 Frames" },
+        { "3000", "3000 Frames" },
+    },
+    "off"
+},
+{
+    "ruffle_benchmark_exit",
+    "Developer > Exit After Benchmark",
+    "Exit After Benchmark",
+    "Closes the core once the benchmark finishes.",
+    "",
+    "developer_settings",
+    {
+        { "false" },
+        { "true" },
+    }
+},
+{
+    "ruffle_audio_dump",
+    "Developer > Dump Audio",
+    "Dump Audio",
+    "While enabled, writes everything the core sends to the frontend to a WAV file in ruffle/ in the save directory. Each time it's enabled starts a new file.",
+    "",
+    "developer_settings",
+    {
+        { "false" },
+        { "true" },
+    }
+},
+{
+    "ruffle_render_diagnostics",
+    "Developer > Write Render Diagnostics",
+    "Write Render Diagnostics",
+    "Writes the graphics adapter's details, extensions, and negotiated features to ruffle/render_diagnostics.txt in the system directory when content is loaded. Useful for reporting driver-specific bugs.",
+    "",
+    "developer_settings",
+    {
+        { "false" },
+        { "true" },
+    }
+},
+{
+    "ruffle_vulkan_validation",
+    "Developer > Vulkan Validation",
+    "Vulkan Validation",
+    "Enables the Vulkan validation layers, if they're installed, and logs what they report. Slows rendering down considerably. Takes effect when content is loaded, and only if the frontend supports Vulkan context negotiation.",
+    "",
+    "developer_settings",
+    {
+        { "false" },
+        { "true" },
+    }
+},
+{
+    "ruffle_audio_visualizer",
+    "Developer > Audio Level Meters",
+    "Audio Level Meters",
+    "Draws the peak level of each audio channel as a meter along the left edge of the screen, to confirm audio is flowing. Not shown by the fallback renderer.",
+    "",
+    "developer_settings",
+    {
+        { "false" },
+        { "true" },
+    }
 }
 )]
 pub struct Ruffle {
@@ -177,7 +863,28 @@ pub struct Ruffle {
     config: Config,
     frontend_preferred_hw_render: retro_hw_context_type,
     queued_events: VecDeque<PlayerEvent>,
+    held_keys: Vec<retro_key>,
+    key_repeat: KeyRepeat,
+    pause_requested: bool,
+    end_watcher: EndWatcher,
+    /// Whether `Config::paused_frame` still has to be shown
+    preview_pending: bool,
+    /// Whether the player is showing `Config::paused_frame` instead of where playback should start
+    preview_shown: bool,
+    /// The stage color last reported to the frontend
+    background_color: Option<[u8; 3]>,
+    /// A frame rate the frontend wouldn't switch to, so it isn't asked again every frame
+    rejected_frame_rate: Option<f64>,
     mouse_state: MouseState,
+    port_device: Option<u32>,
+    /// The device the movie seems to be built for, whose controls are shown until the frontend reports one of ours
+    content_device: Option<u32>,
+    executor: Option<RetroExecutor>,
+    benchmark: Option<Benchmark>,
+    adaptive_quality: AdaptiveQuality,
+    frame_pacer: Option<FramePacer>,
+    frame_limiter: Option<FrameLimiter>,
+    audio_dump: Option<WavWriter>,
     #[cfg(feature = "profiler")]
     tracy_client: Option<Client>,
 }
@@ -192,14 +899,39 @@ impl Ruffle {
             config: Config::new(),
             frontend_preferred_hw_render: retro_hw_context_type::RETRO_HW_CONTEXT_NONE,
             queued_events: VecDeque::with_capacity(16),
+            held_keys: Vec::with_capacity(16),
+            key_repeat: KeyRepeat::default(),
+            pause_requested: false,
+            end_watcher: EndWatcher::default(),
+            preview_pending: false,
+            preview_shown: false,
+            background_color: None,
+            rejected_frame_rate: None,
             mouse_state: MouseState::default(),
+            port_device: None,
+            content_device: None,
+            executor: None,
+            benchmark: None,
+            adaptive_quality: AdaptiveQuality::default(),
+            frame_pacer: None,
+            frame_limiter: None,
+            audio_dump: None,
             #[cfg(feature = "profiler")]
             tracy_client: None,
         }
     }
 }
 
+mod adaptive;
+mod benchmark;
 pub mod config;
 mod core;
+mod ending;
+mod frame_time;
 mod input;
+mod legacy_options;
+mod limiter;
+mod pacing;
+mod parse;
+mod repeat;
 mod state;
@@ -0,0 +1,50 @@
+use ruffle_render::quality::StageQuality;
+
+/// Quality levels to step through, from best to fastest.
+const QUALITY_LEVELS: [StageQuality; 3] = [StageQuality::High, StageQuality::Medium, StageQuality::Low];
+
+/// Audio buffer occupancy (in percent) below which the core is considered to be falling behind.
+const LOW_OCCUPANCY: u32 = 25;
+
+/// Audio buffer occupancy (in percent) above which the core is considered to be keeping up.
+const HEALTHY_OCCUPANCY: u32 = 75;
+
+/// How many consecutive reports are needed before changing quality, to avoid flip-flopping.
+const REPORTS_BEFORE_CHANGE: u32 = 120;
+
+/// Lowers the stage quality while the frontend's audio buffer is chronically starved,
+/// and raises it again once the buffer stays healthy.
+#[derive(Debug, Default)]
+pub struct AdaptiveQuality {
+    level: usize,
+    low_reports: u32,
+    healthy_reports: u32,
+}
+
+impl AdaptiveQuality {
+    /// Returns the quality to switch to, if it should change.
+    pub fn update(&mut self, occupancy: u32, underrun_likely: bool) -> Option<StageQuality> {
+        if underrun_likely || occupancy < LOW_OCCUPANCY {
+            self.low_reports += 1;
+            self.healthy_reports = 0;
+        } else if occupancy > HEALTHY_OCCUPANCY {
+            self.healthy_reports += 1;
+            self.low_reports = 0;
+        } else {
+            self.low_reports = 0;
+            self.healthy_reports = 0;
+        }
+
+        if self.low_reports >= REPORTS_BEFORE_CHANGE && self.level + 1 < QUALITY_LEVELS.len() {
+            self.level += 1;
+            self.low_reports = 0;
+            Some(QUALITY_LEVELS[self.level])
+        } else if self.healthy_reports >= REPORTS_BEFORE_CHANGE && self.level > 0 {
+            self.level -= 1;
+            self.healthy_reports = 0;
+            Some(QUALITY_LEVELS[self.level])
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+/// Times `Player::tick` and `Player::render` over a fixed number of frames,
+/// along with how long polled input waits before the player sees it.
+pub struct Benchmark {
+    target_frames: u32,
+    frames: u32,
+    input_latency: Duration,
+    tick_time: Duration,
+    render_time: Duration,
+    exit_when_done: bool,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct BenchmarkSummary {
+    pub frames: u32,
+    pub average_input_latency: Duration,
+    pub average_tick: Duration,
+    pub average_render: Duration,
+}
+
+impl Benchmark {
+    pub fn new(target_frames: u32, exit_when_done: bool) -> Self {
+        Self {
+            target_frames,
+            frames: 0,
+            input_latency: Duration::ZERO,
+            tick_time: Duration::ZERO,
+            render_time: Duration::ZERO,
+            exit_when_done,
+        }
+    }
+
+    pub fn exit_when_done(&self) -> bool {
+        self.exit_when_done
+    }
+
+    /// Records one frame's timings, returning a summary once the target frame count is reached.
+    pub fn record(
+        &mut self,
+        input_latency: Duration,
+        tick_time: Duration,
+        render_time: Duration,
+    ) -> Option<BenchmarkSummary> {
+        self.frames += 1;
+        self.input_latency += input_latency;
+        self.tick_time += tick_time;
+        self.render_time += render_time;
+
+        if self.frames < self.target_frames {
+            return None;
+        }
+
+        Some(BenchmarkSummary {
+            frames: self.frames,
+            average_input_latency: self.input_latency / self.frames,
+            average_tick: self.tick_time / self.frames,
+            average_render: self.render_time / self.frames,
+        })
+    }
+}
+
+impl Display for BenchmarkSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Benchmark: {} frames, average input latency {:.3}ms, average tick {:.3}ms, average render {:.3}ms",
+            self.frames,
+            self.average_input_latency.as_secs_f64() * 1000.0,
+            self.average_tick.as_secs_f64() * 1000.0,
+            self.average_render.as_secs_f64() * 1000.0
+        )
+    }
+}
@@ -1,11 +1,24 @@
-use crate::options::{FileAccessPolicy, WebBrowserAccess};
+use crate::options::{
+    AnalogCursor, AspectRatioMode, AudioChannels, AudioPrecision, EndBehavior, ExternalInterfaceMode, FallbackBackend,
+    FileAccessPolicy, GuideOverlay, KeyboardPassthrough, LetterboxColor, MouseAxes, PostProcessPreset, ResamplerQuality,
+    RightClickMode, WebBrowserAccess,
+};
 use ruffle_core::config::Letterbox;
-use ruffle_core::LoadBehavior;
+use ruffle_core::{LoadBehavior, StageAlign};
 use std::time::Duration;
 
+#[derive(Clone)]
 pub struct Config {
     pub(crate) autoplay: bool,
+    /// 1-based frame shown while not autoplaying
+    pub(crate) paused_frame: u16,
+    pub(crate) end_behavior: EndBehavior,
     pub(crate) letterbox: Letterbox,
+    pub(crate) letterbox_color: LetterboxColor,
+    pub(crate) stage_align: Option<StageAlign>,
+    pub(crate) aspect_ratio_mode: AspectRatioMode,
+    pub(crate) post_process: PostProcessPreset,
+    pub(crate) overlay: GuideOverlay,
     pub(crate) max_execution_duration: Duration,
     pub(crate) warn_on_unsupported_content: bool,
     pub(crate) load_behavior: LoadBehavior,
@@ -13,15 +26,67 @@ pub struct Config {
     pub(crate) web_browser_access: WebBrowserAccess,
     pub(crate) spoofed_url: Option<String>,
     pub(crate) sample_rate: u32,
+    /// Master volume, from 0.0 (muted) to 1.0
+    pub(crate) volume: f32,
+    pub(crate) audio_precision: AudioPrecision,
+    pub(crate) audio_channels: AudioChannels,
+    pub(crate) resampler_quality: ResamplerQuality,
+    pub(crate) audio_mute: bool,
     pub(crate) msaa: u8,
+    pub(crate) text_scale: f64,
+    pub(crate) render_scale: f64,
+    pub(crate) power_save: bool,
+    pub(crate) vulkan_frames: Option<u32>,
+    pub(crate) max_bitmap_memory: Option<usize>,
+    pub(crate) show_render_status: bool,
+    pub(crate) fallback_backend: FallbackBackend,
+    pub(crate) benchmark_frames: Option<u32>,
+    pub(crate) benchmark_exit: bool,
+    pub(crate) external_interface: ExternalInterfaceMode,
+    pub(crate) allow_quit: bool,
+    pub(crate) clipboard: bool,
+    /// Whether the movie may make its own http(s) requests
+    pub(crate) network_access: bool,
     pub(crate) upgrade_to_https: bool,
+    pub(crate) http_headers: Vec<(String, String)>,
+    pub(crate) http_cache: bool,
+    /// `None` for no limit
+    pub(crate) max_requests: Option<usize>,
+    pub(crate) local_only: bool,
+    pub(crate) mouse_confine: bool,
+    pub(crate) software_cursor: bool,
+    pub(crate) pause_key: bool,
+    /// `None` if keys shouldn't repeat
+    pub(crate) key_repeat_delay: Option<Duration>,
+    pub(crate) key_repeat_interval: Duration,
+    pub(crate) keyboard_passthrough: KeyboardPassthrough,
+    pub(crate) mouse_sensitivity: f32,
+    pub(crate) wheel_invert: bool,
+    pub(crate) auto_device: bool,
+    pub(crate) analog_cursor: AnalogCursor,
+    pub(crate) mouse_axes: MouseAxes,
+    pub(crate) right_click: RightClickMode,
+    pub(crate) adaptive_quality: bool,
+    pub(crate) frame_pacing: bool,
+    pub(crate) max_fps: Option<u32>,
+    pub(crate) render_diagnostics: bool,
+    pub(crate) vulkan_validation: bool,
+    pub(crate) audio_visualizer: bool,
+    pub(crate) audio_dump: bool,
 }
 
 impl Config {
     pub fn new() -> Self {
         Self {
             autoplay: defaults::AUTOPLAY,
+            paused_frame: defaults::PAUSED_FRAME,
+            end_behavior: defaults::END_BEHAVIOR,
             letterbox: defaults::LETTERBOX,
+            letterbox_color: defaults::LETTERBOX_COLOR,
+            stage_align: defaults::STAGE_ALIGN,
+            aspect_ratio_mode: defaults::ASPECT_RATIO_MODE,
+            post_process: defaults::POST_PROCESS,
+            overlay: defaults::OVERLAY,
             max_execution_duration: defaults::MAX_EXECUTION_DURATION,
             warn_on_unsupported_content: defaults::WARN_ON_UNSUPPORTED_CONTENT,
             load_behavior: defaults::LOAD_BEHAVIOR,
@@ -29,26 +94,118 @@ impl Config {
             web_browser_access: defaults::WEB_BROWSER_ACCESS,
             spoofed_url: None,
             sample_rate: defaults::SAMPLE_RATE,
+            volume: defaults::VOLUME,
+            audio_precision: defaults::AUDIO_PRECISION,
+            audio_channels: defaults::AUDIO_CHANNELS,
+            resampler_quality: defaults::RESAMPLER_QUALITY,
+            audio_mute: defaults::AUDIO_MUTE,
             msaa: defaults::MSAA,
+            text_scale: defaults::TEXT_SCALE,
+            render_scale: defaults::RENDER_SCALE,
+            power_save: defaults::POWER_SAVE,
+            vulkan_frames: defaults::VULKAN_FRAMES,
+            max_bitmap_memory: defaults::MAX_BITMAP_MEMORY,
+            show_render_status: defaults::SHOW_RENDER_STATUS,
+            fallback_backend: defaults::FALLBACK_BACKEND,
+            benchmark_frames: defaults::BENCHMARK_FRAMES,
+            benchmark_exit: defaults::BENCHMARK_EXIT,
+            external_interface: defaults::EXTERNAL_INTERFACE,
+            allow_quit: defaults::ALLOW_QUIT,
+            clipboard: defaults::CLIPBOARD,
+            network_access: defaults::NETWORK_ACCESS,
             upgrade_to_https: defaults::UPGRADE_TO_HTTPS,
+            http_headers: Vec::new(),
+            http_cache: defaults::HTTP_CACHE,
+            max_requests: defaults::MAX_REQUESTS,
+            local_only: false,
+            mouse_confine: defaults::MOUSE_CONFINE,
+            software_cursor: defaults::SOFTWARE_CURSOR,
+            pause_key: defaults::PAUSE_KEY,
+            key_repeat_delay: defaults::KEY_REPEAT_DELAY,
+            key_repeat_interval: defaults::KEY_REPEAT_INTERVAL,
+            keyboard_passthrough: defaults::KEYBOARD_PASSTHROUGH,
+            mouse_sensitivity: defaults::MOUSE_SENSITIVITY,
+            wheel_invert: defaults::WHEEL_INVERT,
+            auto_device: defaults::AUTO_DEVICE,
+            analog_cursor: defaults::ANALOG_CURSOR,
+            mouse_axes: defaults::MOUSE_AXES,
+            right_click: defaults::RIGHT_CLICK,
+            adaptive_quality: defaults::ADAPTIVE_QUALITY,
+            frame_pacing: defaults::FRAME_PACING,
+            max_fps: defaults::MAX_FPS,
+            render_diagnostics: defaults::RENDER_DIAGNOSTICS,
+            vulkan_validation: defaults::VULKAN_VALIDATION,
+            audio_visualizer: defaults::AUDIO_VISUALIZER,
+            audio_dump: defaults::AUDIO_DUMP,
         }
     }
 }
 
 pub mod defaults {
     use ruffle_core::config::Letterbox;
-    use ruffle_core::LoadBehavior;
+    use ruffle_core::{LoadBehavior, StageAlign};
     use std::time::Duration;
-    use crate::options::{FileAccessPolicy, WebBrowserAccess};
+    use crate::options::{
+        AnalogCursor, AspectRatioMode, AudioChannels, AudioPrecision, EndBehavior, ExternalInterfaceMode,
+        FallbackBackend, FileAccessPolicy, GuideOverlay, KeyboardPassthrough, LetterboxColor, MouseAxes,
+        PostProcessPreset, ResamplerQuality, RightClickMode, WebBrowserAccess,
+    };
 
+    pub const USE_DEFAULTS: bool = false;
     pub const AUTOPLAY: bool = true;
+    pub const PAUSED_FRAME: u16 = 1;
+    pub const END_BEHAVIOR: EndBehavior = EndBehavior::Loop;
     pub const LETTERBOX: Letterbox = Letterbox::Fullscreen;
+    pub const LETTERBOX_COLOR: LetterboxColor = LetterboxColor::Black;
+    pub const STAGE_ALIGN: Option<StageAlign> = None;
+    pub const ASPECT_RATIO_MODE: AspectRatioMode = AspectRatioMode::Geometry;
+    pub const POST_PROCESS: PostProcessPreset = PostProcessPreset::Off;
+    pub const OVERLAY: GuideOverlay = GuideOverlay::Off;
     pub const MAX_EXECUTION_DURATION: Duration = Duration::from_secs(15);
     pub const MSAA: u8 = 0;
+    pub const TEXT_SCALE: f64 = 1.0;
+    pub const RENDER_SCALE: f64 = 1.0;
+    pub const POWER_SAVE: bool = false;
+    pub const VULKAN_FRAMES: Option<u32> = None;
+    pub const MAX_BITMAP_MEMORY: Option<usize> = None;
+    pub const SHOW_RENDER_STATUS: bool = false;
+    pub const FALLBACK_BACKEND: FallbackBackend = FallbackBackend::Auto;
+    pub const BENCHMARK_FRAMES: Option<u32> = None;
+    pub const BENCHMARK_EXIT: bool = false;
+    pub const AUDIO_PRECISION: AudioPrecision = AudioPrecision::Int16;
+    pub const AUDIO_CHANNELS: AudioChannels = AudioChannels::Stereo;
+    pub const RESAMPLER_QUALITY: ResamplerQuality = ResamplerQuality::Linear;
+    pub const AUDIO_MUTE: bool = false;
+    pub const EXTERNAL_INTERFACE: ExternalInterfaceMode = ExternalInterfaceMode::Log;
     pub const WARN_ON_UNSUPPORTED_CONTENT: bool = true;
     pub const LOAD_BEHAVIOR: LoadBehavior = LoadBehavior::Streaming;
     pub const FILE_ACCESS_POLICY: FileAccessPolicy = FileAccessPolicy::Never;
     pub const WEB_BROWSER_ACCESS: WebBrowserAccess = WebBrowserAccess::Ignore;
     pub const SAMPLE_RATE: u32 = 44100;
+    pub const VOLUME: f32 = 1.0;
+    pub const NETWORK_ACCESS: bool = false;
     pub const UPGRADE_TO_HTTPS: bool = true;
+    pub const HTTP_CACHE: bool = true;
+    pub const MAX_REQUESTS: Option<usize> = Some(6);
+    pub const ALLOW_QUIT: bool = false;
+    pub const CLIPBOARD: bool = true;
+    pub const MOUSE_CONFINE: bool = true;
+    pub const SOFTWARE_CURSOR: bool = false;
+    pub const PAUSE_KEY: bool = true;
+    pub const KEY_REPEAT_DELAY: Option<Duration> = None;
+    pub const KEY_REPEAT_INTERVAL: Duration = Duration::from_nanos(1_000_000_000 / 30);
+    pub const KEYBOARD_PASSTHROUGH: KeyboardPassthrough = KeyboardPassthrough::All;
+    pub const MOUSE_SENSITIVITY: f32 = 1.0;
+    pub const WHEEL_INVERT: bool = false;
+    pub const AUTO_DEVICE: bool = true;
+    pub const ANALOG_CURSOR: AnalogCursor = AnalogCursor::Linear;
+    pub const MOUSE_AXES: MouseAxes = MouseAxes::Normal;
+    pub const RIGHT_CLICK: RightClickMode = RightClickMode::ContextMenu;
+    pub const ADAPTIVE_QUALITY: bool = false;
+    pub const FRAME_PACING: bool = false;
+    pub const MAX_FPS: Option<u32> = None;
+    pub const RENDER_DIAGNOSTICS: bool = false;
+    pub const VULKAN_VALIDATION: bool = false;
+    pub const AUDIO_VISUALIZER: bool = false;
+    pub const AUDIO_DUMP: bool = false;
 }
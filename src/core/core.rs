@@ -1,14 +1,15 @@
+use std::borrow::Cow;
 use std::collections::VecDeque;
 use std::error::Error;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::ops::DerefMut;
 use std::panic;
+use std::path::{Path, PathBuf};
 use std::ptr;
 use std::slice::from_raw_parts;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use euclid::Vector2D;
 use futures::executor::block_on;
 use log::{debug, error, info, warn};
 
@@ -17,36 +18,56 @@ use profiling;
 
 #[cfg(feature = "profiler")]
 use profiling::tracy_client;
-use ruffle_core::backend::navigator::NullNavigatorBackend;
+use ruffle_core::backend::audio::AudioBackend;
 use ruffle_core::backend::storage::MemoryStorageBackend;
 use ruffle_core::config::Letterbox;
+use ruffle_core::events::MouseButton;
+use ruffle_core::display_object::{StageDisplayState, TDisplayObject};
+use ruffle_core::swf::FileAttributes;
 use ruffle_core::tag_utils::SwfMovie;
-use ruffle_core::{LoadBehavior, Player, PlayerBuilder, PlayerEvent};
+use ruffle_core::{LoadBehavior, Player, PlayerBuilder, PlayerEvent, StageAlign};
 use ruffle_render::backend::ViewportDimensions;
 use ruffle_video_software::backend::SoftwareVideoBackend;
 use rust_libretro::contexts::*;
 use rust_libretro::core::Core;
-use rust_libretro::environment::get_save_directory;
+use rust_libretro::environment::{get_save_directory, get_system_directory};
 use rust_libretro::sys::retro_hw_context_type::*;
 use rust_libretro::sys::*;
-use rust_libretro::types::{MessageProgress, PixelFormat, SystemInfo};
+use rust_libretro::types::{PixelFormat, SystemInfo};
 use rust_libretro::{anyhow, environment};
 use thiserror::Error as ThisError;
+use url::Url;
 
 use crate::backend::audio::RetroAudioBackend;
+use crate::backend::cache::HttpCache;
+use crate::backend::external::RetroExternalInterfaceProvider;
 use crate::backend::log::RetroLogBackend;
-use crate::backend::navigator::RetroNavigatorBackend;
+use crate::backend::navigator::{RetroExecutor, RetroNavigatorBackend};
 use crate::backend::render::opengl::OpenGlWgpuRenderBackend;
-use crate::backend::render::vulkan::VulkanWgpuRenderBackend;
+use crate::backend::render::software::SoftwareWgpuRenderBackend;
+use crate::backend::render::vulkan::{negotiation, VulkanWgpuRenderBackend};
 use crate::backend::render::HardwareRenderError::UnsupportedHardwareContext;
 use crate::backend::render::{enable_hw_render, enable_hw_render_negotiation_interface};
-use crate::backend::storage::RetroVfsStorageBackend;
-use crate::backend::ui::RetroUiBackend;
-use crate::core::config::defaults;
+use crate::backend::storage::{copy_vfs_interface, RetroVfsStorageBackend, VFS_VERSIONS};
+use crate::backend::ui::{RetroUiBackend, DOWNLOAD_FAILED_MESSAGE};
+use crate::core::adaptive::AdaptiveQuality;
+use crate::core::benchmark::Benchmark;
+use crate::core::config::{defaults, Config};
+use crate::core::ending::{EndAction, EndWatcher};
+use crate::core::frame_time;
+use crate::core::limiter::FrameLimiter;
+use crate::core::pacing::FramePacer;
+use crate::core::state::ExitReason;
 use crate::core::state::PlayerState::*;
-use crate::core::{input, Ruffle};
-use crate::options::{FileAccessPolicy, WebBrowserAccess};
+use crate::core::{input, legacy_options, parse, Ruffle};
+use crate::options::{
+    AnalogCursor, AspectRatioMode, AudioChannels, AudioPrecision, EndBehavior, ExternalInterfaceMode, FallbackBackend,
+    FileAccessPolicy, GuideOverlay, KeyboardPassthrough, MouseAxes, PostProcessPreset, ResamplerQuality, RightClickMode,
+    WebBrowserAccess,
+};
 use crate::util::mouse::MouseState;
+use crate::util::swf::ControlHint;
+use crate::util::wav::WavWriter;
 use crate::{built_info, util};
 
 #[derive(ThisError, Debug)]
@@ -56,6 +77,39 @@ pub enum CoreError {
 
     #[error("Failed to load SWF")]
     FailedToLoadSwf,
+
+    #[error("SWF is encrypted or protected")]
+    ProtectedSwf,
+
+    #[error("Failed to download SWF")]
+    FailedToDownloadSwf,
+}
+
+/// Every playable SWF starts with one of these signatures
+/// (uncompressed, zlib-compressed, or LZMA-compressed, respectively).
+const SWF_SIGNATURES: [&[u8]; 3] = [b"FWS", b"CWS", b"ZWS"];
+
+/// Returns `true` if the data doesn't look like a SWF at all,
+/// which usually means it was encrypted or wrapped by a protection tool.
+fn is_protected_swf(data: &[u8]) -> bool {
+    !SWF_SIGNATURES.iter().any(|signature| data.starts_with(signature))
+}
+
+/// Grows the viewport's width or height (never shrinks it) so that it has the given aspect ratio.
+fn pad_to_aspect_ratio(dimensions: ViewportDimensions, aspect_ratio: f64) -> ViewportDimensions {
+    let width = f64::from(dimensions.width);
+    let height = f64::from(dimensions.height);
+    let (width, height) = if width / height < aspect_ratio {
+        (height * aspect_ratio, height)
+    } else {
+        (width, width / aspect_ratio)
+    };
+
+    ViewportDimensions {
+        width: width.round() as u32,
+        height: height.round() as u32,
+        scale_factor: dimensions.scale_factor,
+    }
 }
 
 impl Core for Ruffle {
@@ -63,7 +117,7 @@ impl Core for Ruffle {
         SystemInfo {
             library_name: CString::new("Ruffle").unwrap(),
             library_version: CString::new(built_info::PKG_VERSION).unwrap(),
-            valid_extensions: CString::new("swf").unwrap(),
+            valid_extensions: CString::new("swf|url").unwrap(),
             need_fullpath: false,
             block_extract: false,
         }
@@ -84,14 +138,17 @@ impl Core for Ruffle {
         #[cfg(feature = "profiler")]
         profiling::scope!("retro_set_environment");
 
-        if let Err(e) = ctx.set_support_no_game(false) {
+        if let Err(e) = ctx.set_support_no_game(true) {
             warn!("RETRO_ENVIRONMENT_SET_SUPPORT_NO_GAME failed: {e}");
         }
 
-        unsafe {
-            if let Err(e) = ctx.enable_vfs_interface(3) {
-                panic!("RETRO_ENVIRONMENT_GET_VFS_INTERFACE failed: {e}");
-            }
+        // Older frontends only offer older versions, which lack some functions; storage works around them
+        let vfs_version = VFS_VERSIONS
+            .into_iter()
+            .find(|&version| unsafe { ctx.enable_vfs_interface(version) }.is_ok());
+        match vfs_version {
+            Some(version) => info!("Using VFS interface v{version}"),
+            None => warn!("RETRO_ENVIRONMENT_GET_VFS_INTERFACE failed, SharedObjects won't be saved"),
         }
 
         let ctx = GenericContext::from(ctx);
@@ -103,25 +160,50 @@ impl Core for Ruffle {
             *environ_cb
         });
 
-        self.vfs.replace(unsafe {
+        self.vfs.replace(vfs_version.and_then(|version| unsafe {
             let vfs = environment::get_vfs_interface(
                 self.environ_cb.get(),
                 retro_vfs_interface_info {
-                    required_interface_version: 3,
+                    required_interface_version: version,
                     iface: ptr::null_mut(),
                 },
             );
 
             match vfs {
                 Ok(vfs) if vfs.iface.is_null() => None,
-                Ok(vfs) => Some(*vfs.iface),
+                Ok(vfs) => Some(copy_vfs_interface(vfs.iface, version)),
                 _ => None,
             }
-        });
+        }));
 
         if let Err(e) = ctx.set_controller_info(input::CONTROLLER_INFO) {
             warn!("RETRO_ENVIRONMENT_SET_CONTROLLER_INFO failed: {e}");
         }
+
+        // The derived set_core_options only sends v2 options, so older frontends get them without categories;
+        // that's why every option's plain description is prefixed with its category (e.g. "Video > Letterbox")
+        let environ_cb = self.environ_cb.get();
+        let result = match unsafe { environment::get_core_options_version(environ_cb) } {
+            Ok(version) if version >= 2 => {
+                debug!("Frontend supports core options v{version}, using categories");
+                Ok(())
+            }
+            Ok(1) => {
+                info!("Frontend supports core options v1, sending options without categories");
+                unsafe { legacy_options::set_core_options_v1(environ_cb) }
+            }
+            Ok(version) => {
+                info!("Frontend supports core options v{version}, sending options as plain variables");
+                unsafe { legacy_options::set_variables(environ_cb) }
+            }
+            Err(e) => {
+                warn!("RETRO_ENVIRONMENT_GET_CORE_OPTIONS_VERSION failed, sending options as plain variables: {e}");
+                unsafe { legacy_options::set_variables(environ_cb) }
+            }
+        };
+        if let Err(e) = result {
+            error!("Failed to send core options to the frontend: {e}");
+        }
     }
 
     fn on_init(&mut self, ctx: &mut InitContext) {
@@ -154,48 +236,242 @@ impl Core for Ruffle {
         }
     }
 
-    fn on_set_controller_port_device(&mut self, _port: u32, _device: u32, _ctx: &mut GenericContext) {}
+    fn on_set_controller_port_device(&mut self, port: u32, device: u32, ctx: &mut GenericContext) {
+        debug!("Core::on_set_controller_port_device(port = {port}, device = {device})");
+        if port != 0 {
+            warn!("Ignoring device {device} for unsupported port {port}");
+            return;
+        }
+
+        self.port_device = Some(device);
+        if let Err(e) = ctx.set_input_descriptors(input::input_descriptors(self.descriptor_device())) {
+            warn!("RETRO_ENVIRONMENT_SET_INPUT_DESCRIPTORS failed: {e}");
+        }
+    }
 
     fn on_reset(&mut self, _ctx: &mut ResetContext) {
         debug!("Core::on_reset()");
+        self.release_held_keys();
     }
 
-    fn on_run(&mut self, ctx: &mut RunContext, delta_us: Option<i64>) {
+    fn on_run(&mut self, ctx: &mut RunContext, _delta_us: Option<i64>) {
         #[cfg(feature = "profiler")]
         let run_span = tracy_client::span!("retro_run", 0);
-        if let Exiting = self.player {
-            error!("Hardware context lost, core will now exit.");
+        if let Exiting(reason) = self.player {
             let ctx = GenericContext::from(ctx);
-            self.notify_context_lost(&ctx);
+            match reason {
+                ExitReason::ContextLost => {
+                    error!("Hardware context lost, core will now exit.");
+                    self.notify_context_lost(&ctx);
+                }
+                ExitReason::Requested => {
+                    info!("Exit requested, core will now exit.");
+                    Self::notify_info(&ctx, EXIT_REQUESTED_MESSAGE, EXIT_REQUESTED_DURATION, EXIT_REQUESTED_PRIORITY);
+                }
+            }
             ctx.shutdown();
             return;
         }
 
+        // Not every frontend honors the frame time callback, so assume we're running at the movie's frame rate.
+        // The callback is registered by frame_time rather than rust-libretro, so its `delta_us` is never set.
+        let frame_time_us = self.av_info.as_ref().and_then(Self::frame_time_us);
+        let delta_us = frame_time::take().or(frame_time_us);
+        // retro_run isn't called while the frontend's menu is open; catching up on all that time at once
+        // would skip the movie and its streamed audio ahead, so pick up where it left off instead
+        let resumed = match (delta_us, frame_time_us) {
+            (Some(delta), Some(frame_time)) => delta > frame_time * MAX_CATCH_UP_FRAMES,
+            _ => false,
+        };
+        let delta_us = match (delta_us, frame_time_us) {
+            (Some(delta), Some(frame_time)) if resumed => {
+                debug!("{delta}us passed since the last frame, resuming without catching up");
+                Some(frame_time)
+            }
+            _ => delta_us,
+        };
+        let delta_us = match (&mut self.frame_pacer, delta_us) {
+            (Some(pacer), Some(delta)) => Some(pacer.pace(delta)),
+            _ => delta_us,
+        };
+        let delta_us = match (&mut self.frame_limiter, delta_us) {
+            (Some(limiter), Some(delta)) => match limiter.admit(delta, Instant::now()) {
+                Some(delta) => Some(delta),
+                None => {
+                    // Too soon to tick again; show the last frame and leave the time for the next tick
+                    ctx.dupe_frame();
+                    None
+                }
+            },
+            _ => delta_us,
+        };
+
         #[cfg(feature = "profiler")]
         if let Some(delta) = delta_us {
             run_span.emit_value(delta as u64);
         }
 
+        let mut exit_requested = false;
+        let mut frame_rate_changed = None;
         if let (Active(player), Some(delta)) = (&mut self.player, delta_us) {
+            if let Some(executor) = &self.executor {
+                // Must happen before locking the player, since loader tasks lock it themselves
+                #[cfg(feature = "profiler")]
+                profiling::scope!("RetroExecutor::tick");
+                executor.tick();
+            }
+
             let av_info = self.av_info.as_ref().expect("av_info should've been initialized");
             let mut player = player.lock().expect("Cannot reenter");
-            Self::handle_input(&mut player, &mut self.mouse_state, &av_info.geometry, &mut self.queued_events, ctx);
+            let input_start = Instant::now();
+            match self.key_repeat.poll(input_start, self.config.key_repeat_interval) {
+                Some((keycode, count)) if self.held_keys.contains(&keycode) => {
+                    for _ in 0..count {
+                        self.queued_events.push_back(PlayerEvent::KeyDown {
+                            key_code: util::keyboard::to_key_code(keycode),
+                            key_char: None,
+                        });
+                    }
+                }
+                // The frontend lost focus and never reported the release
+                Some(_) => self.key_repeat.clear(),
+                None => {}
+            }
 
-            {
+            let had_input = Self::handle_input(
+                &mut player,
+                &mut self.mouse_state,
+                &mut self.held_keys,
+                self.port_device,
+                &self.config,
+                &av_info.geometry,
+                &mut self.queued_events,
+                resumed,
+                ctx,
+            );
+
+            if std::mem::take(&mut self.pause_requested) {
+                let playing = !player.is_playing();
+                player.set_is_playing(playing);
+                let message = if playing { RESUMED_MESSAGE } else { PAUSED_MESSAGE };
+                info!("{message}");
+
+                let ctx = GenericContext::from(&mut *ctx);
+                Self::notify_info(&ctx, message, PAUSE_DURATION, PAUSE_PRIORITY);
+            }
+
+            let tick_start = Instant::now();
+            if player.is_playing() {
                 #[cfg(feature = "profiler")]
                 profiling::scope!("Player::tick");
 
+                if std::mem::take(&mut self.preview_shown) {
+                    // The preview frame was only for show
+                    player.mutate_with_update_context(|context| {
+                        if let Some(root) = context.stage.root_clip().as_movie_clip() {
+                            root.goto_frame(context, 1, false);
+                        }
+                    });
+                }
+
                 player.tick((delta as f64) / 1000.0);
                 // Ruffle wants milliseconds, we have microseconds.
+
+                let end_behavior = self.config.end_behavior;
+                let end_watcher = &mut self.end_watcher;
+                let end_action = player.mutate_with_update_context(|context| {
+                    let root = context.stage.root_clip().as_movie_clip()?;
+                    let action =
+                        end_watcher.update(end_behavior, root.current_frame(), root.total_frames(), root.playing());
+                    match action {
+                        Some(EndAction::Stop) => root.stop(context),
+                        Some(EndAction::Restart) => root.goto_frame(context, 1, false),
+                        Some(EndAction::Exit) | None => {}
+                    }
+                    action
+                });
+
+                if end_action == Some(EndAction::Exit) {
+                    info!("Movie reached its last frame, closing the core");
+                    exit_requested = true;
+                }
+            } else if self.preview_pending {
+                let frame = self.config.paused_frame;
+                let shown = player.mutate_with_update_context(|context| {
+                    let root = context.stage.root_clip().as_movie_clip()?;
+                    root.goto_frame(context, frame.min(root.total_frames()), true);
+                    Some(())
+                });
+
+                // The root movie may not exist until the first frame after loading
+                if shown.is_some() {
+                    self.preview_pending = false;
+                    self.preview_shown = true;
+                }
+            }
+
+            Self::report_background_color(&mut player, &mut self.background_color, ctx);
+
+            // The movie may not redraw just because the mouse moved, but the cursor drawn over it has to
+            let cursor_changed = Self::show_cursor(&mut player, self.config.software_cursor, &self.mouse_state);
+
+            let render_start = Instant::now();
+            let rendered = Self::render_graphics(&mut player, av_info, cursor_changed, ctx);
+            let render_end = Instant::now();
+
+            Self::send_audio(&mut player, delta, self.audio_dump.as_mut(), ctx);
+            Self::show_audio_levels(&mut player, self.config.audio_visualizer);
+
+            // Stage.frameRate can be changed by the movie at any time
+            let frame_rate = player.frame_rate();
+            if frame_rate != av_info.timing.fps
+                && Some(frame_rate) != self.rejected_frame_rate
+                && frame_rate.is_finite()
+                && frame_rate >= MIN_FRAME_RATE
+            {
+                frame_rate_changed = Some(frame_rate);
             }
 
-            Self::render_graphics(&mut player, av_info, ctx);
+            let summary = self.benchmark.as_mut().and_then(|benchmark| {
+                benchmark.record(
+                    tick_start - input_start,
+                    render_start - tick_start,
+                    render_end - render_start,
+                )
+            });
+            if let Some(summary) = summary {
+                let summary = summary.to_string();
+                info!("{summary}");
 
-            Self::send_audio(&mut player, ctx);
+                let ctx = GenericContext::from(&mut *ctx);
+                Self::notify_info(&ctx, &summary, BENCHMARK_DURATION, BENCHMARK_PRIORITY);
+                exit_requested |= self.benchmark.take().map_or(false, |benchmark| benchmark.exit_when_done());
+            }
+
+            if self.config.power_save && !had_input && !rendered {
+                Self::idle(av_info);
+            }
 
             // TODO: React to changed settings
         }
 
+        if let Some(frame_rate) = frame_rate_changed {
+            self.update_frame_rate(frame_rate);
+        }
+
+        // WAV files can't grow past 4 GiB, so long sessions carry on in a new one
+        if self.audio_dump.as_ref().map_or(false, WavWriter::is_full) {
+            info!("Audio dump reached the WAV size limit, starting a new file");
+            self.audio_dump = None;
+            self.update_audio_dump();
+        }
+
+        if exit_requested {
+            // Handled at the start of the next frame, once the player is no longer borrowed
+            self.shut_down_player();
+            self.player = Exiting(ExitReason::Requested);
+        }
+
         #[cfg(feature = "profiler")]
         profiling::finish_frame!();
     }
@@ -206,32 +482,186 @@ impl Core for Ruffle {
         #[cfg(feature = "profiler")]
         profiling::scope!("retro_load_game");
         ctx.set_pixel_format(PixelFormat::XRGB8888)?;
-        ctx.enable_frame_time_callback((1000000.0f64 / 60.0).round() as retro_usec_t)?;
 
-        enable_hw_render(ctx, self.frontend_preferred_hw_render)?;
-        enable_hw_render_negotiation_interface(ctx, self.frontend_preferred_hw_render)?;
+        let hw_render_enabled = match enable_hw_render(ctx, self.frontend_preferred_hw_render) {
+            Ok(()) => {
+                enable_hw_render_negotiation_interface(
+                    ctx,
+                    self.frontend_preferred_hw_render,
+                    self.config.vulkan_validation,
+                );
+                true
+            }
+            Err(e) => {
+                warn!("Hardware rendering is unavailable, using the fallback renderer instead: {e}");
+                false
+            }
+        };
         let ctx = GenericContext::from(ctx);
 
-        ctx.set_input_descriptors(input::INPUT_DESCRIPTORS)?;
+        ctx.set_input_descriptors(input::input_descriptors(self.descriptor_device()))?;
         ctx.enable_keyboard_callback()?;
 
-        let game = game.ok_or(CoreError::NoGameProvided)?;
+        if let Err(e) = ctx.enable_audio_buffer_status_callback() {
+            warn!("RETRO_ENVIRONMENT_SET_AUDIO_BUFFER_STATUS_CALLBACK failed, adaptive quality won't work: {e}");
+        }
+
+        Self::report_capabilities(&ctx);
 
         // TODO: log the game's name to the profiler with Span.emit_value
 
-        let buffer = unsafe { from_raw_parts(game.data as *const u8, game.size as usize) };
-        let movie = SwfMovie::from_data(buffer, "http://localhost".to_string(), None)
+        let game_path = game
+            .filter(|game| !game.path.is_null())
+            .and_then(|game| unsafe { CStr::from_ptr(game.path) }.to_str().ok());
+        let game_data = game
+            .filter(|game| !game.data.is_null())
+            .map(|game| unsafe { from_raw_parts(game.data as *const u8, game.size as usize) });
+
+        // Headers come from a sidecar next to the content, and apply to downloading the movie itself too
+        if let Some(path) = game_path {
+            self.config.http_headers = util::sidecar::read_http_headers(Path::new(path));
+        }
+
+        let remote_url = game_path
+            .zip(game_data)
+            .and_then(|(path, data)| util::fetch::remote_url(Path::new(path), data));
+
+        let (buffer, path) = match (game_data, &remote_url) {
+            (Some(_), Some(url)) => match util::fetch::fetch_movie(url.clone(), &self.config) {
+                Ok(buffer) => (Cow::Owned(buffer), None),
+                Err(e) => {
+                    error!("Failed to download {url}: {e}");
+                    Self::notify_error(
+                        &ctx,
+                        DOWNLOAD_FAILED_MESSAGE,
+                        DOWNLOAD_FAILED_DURATION,
+                        DOWNLOAD_FAILED_PRIORITY,
+                    );
+                    return Err(CoreError::FailedToDownloadSwf.into());
+                }
+            },
+            (Some(buffer), None) => (Cow::Borrowed(buffer), game_path.map(PathBuf::from)),
+            (None, _) => {
+                let (buffer, path) = self.load_home_movie(&ctx)?;
+                self.config.http_headers = util::sidecar::read_http_headers(&path);
+                (Cow::Owned(buffer), Some(path))
+            }
+        };
+
+        if is_protected_swf(&buffer) {
+            Self::notify_error(&ctx, PROTECTED_SWF_MESSAGE, PROTECTED_SWF_DURATION, PROTECTED_SWF_PRIORITY);
+            return Err(CoreError::ProtectedSwf.into());
+        }
+
+        // Relative URLs (e.g. loadMovieNum("child.swf", 1)) should resolve next to the movie
+        let movie_url = remote_url
+            .or_else(|| path.as_ref().and_then(|path| Url::from_file_path(path).ok()))
+            .unwrap_or_else(|| Url::parse("http://localhost").expect("Hard-coded URL should parse"));
+
+        let movie = SwfMovie::from_data(&buffer, movie_url.to_string(), None)
             .ok()
             .ok_or(CoreError::FailedToLoadSwf)?;
 
+        // The frontend gets frames at the movie's size, and scales them to fit the screen
+        let (movie_width, movie_height) = (movie.width().to_pixels(), movie.height().to_pixels());
+        if !(movie_width >= 1.0 && movie_height >= 1.0) {
+            // A malformed header; render targets and the frontend both need at least one pixel
+            warn!("Movie reports a stage size of {movie_width}x{movie_height}, clamping it to at least 1x1");
+        }
+
+        let scale = self.config.render_scale;
         let dimensions = ViewportDimensions {
-            width: movie.width().to_pixels().round() as u32,
-            height: movie.height().to_pixels().round() as u32,
-            scale_factor: 1.0f64, // TODO: figure this out
+            width: ((movie_width * scale).round() as u32).max(1),
+            height: ((movie_height * scale).round() as u32).max(1),
+            scale_factor: scale,
+        };
+
+        // Ruffle centers the stage in the padded viewport and letterboxes it as configured
+        let dimensions = match self.config.aspect_ratio_mode.padded_aspect_ratio() {
+            Some(aspect_ratio) => pad_to_aspect_ratio(dimensions, aspect_ratio),
+            None => dimensions,
+        };
+
+        // Supersampled; the renderer scales each frame back down to `dimensions` before the frontend gets it
+        let text_scale = self.config.text_scale;
+        let viewport = ViewportDimensions {
+            width: (f64::from(dimensions.width) * text_scale).round() as u32,
+            height: (f64::from(dimensions.height) * text_scale).round() as u32,
+            scale_factor: dimensions.scale_factor * text_scale,
         };
 
         let environ_cb = self.environ_cb.get();
 
+        self.config.local_only = match util::swf::file_attributes(&movie) {
+            Some(attributes) => {
+                info!("Movie has FileAttributes {attributes:?}");
+                if attributes.contains(FileAttributes::IS_ACTION_SCRIPT_3) && self.config.warn_on_unsupported_content {
+                    warn!("Movie uses ActionScript 3, which Ruffle only partially supports");
+                }
+
+                // Without this flag, Flash Player only lets local movies access the local file system
+                !attributes.contains(FileAttributes::USE_NETWORK_SANDBOX)
+            }
+            None => {
+                debug!("Movie has no FileAttributes tag");
+                false
+            }
+        };
+
+        let version = movie.version();
+        if version > util::swf::NEWEST_WELL_SUPPORTED_VERSION && self.config.warn_on_unsupported_content {
+            let flash_player = util::swf::flash_player_version(version);
+            info!("Movie targets SWF version {version} (Flash Player {flash_player})");
+            let message = format!(
+                "This movie targets Flash Player {flash_player}.\nIt may use features that Ruffle doesn't support yet."
+            );
+            Self::notify_info(&ctx, &message, NEW_SWF_VERSION_DURATION, NEW_SWF_VERSION_PRIORITY);
+        }
+
+        for codec in util::swf::video_codecs(&movie) {
+            if codec.is_supported() {
+                info!("Movie has video encoded with {}", codec.name());
+            } else {
+                warn!("Movie has video encoded with {} (codec {}), which Ruffle can't decode", codec.name(), codec.0);
+                if self.config.warn_on_unsupported_content {
+                    let message = format!("This movie's video uses {}, which Ruffle can't play yet.", codec.name());
+                    Self::notify_info(&ctx, &message, UNSUPPORTED_VIDEO_DURATION, UNSUPPORTED_VIDEO_PRIORITY);
+                }
+            }
+        }
+
+        self.content_device = None;
+        if self.config.auto_device {
+            let hint = util::swf::control_hint(&movie);
+            self.content_device = match hint {
+                Some(ControlHint::Keyboard) => Some(RETRO_DEVICE_KEYBOARD),
+                Some(ControlHint::Mouse) => Some(RETRO_DEVICE_MOUSE),
+                None => None,
+            };
+
+            match hint {
+                Some(hint) if input::is_offered(self.port_device) => {
+                    info!("Movie seems to be played with the {hint:?}, but the frontend already chose a device");
+                }
+                Some(hint) => {
+                    info!("Movie seems to be played with the {hint:?}, showing its controls until a device is chosen");
+                    if let Err(e) = ctx.set_input_descriptors(input::input_descriptors(self.descriptor_device())) {
+                        warn!("RETRO_ENVIRONMENT_SET_INPUT_DESCRIPTORS failed: {e}");
+                    }
+                }
+                None => debug!("Movie shows no preference for keyboard or mouse"),
+            }
+        }
+
+        // A malformed SWF can report a frame rate of 0, which would break frame timing and audio math
+        let (frame_rate, frame_rate_override) = match f64::from(movie.frame_rate()) {
+            fps if fps.is_finite() && fps >= MIN_FRAME_RATE => (fps, None),
+            fps => {
+                warn!("Movie reports an invalid frame rate of {fps}, using {MIN_FRAME_RATE} instead");
+                (MIN_FRAME_RATE, Some(MIN_FRAME_RATE))
+            }
+        };
+
         self.av_info = Some(retro_system_av_info {
             geometry: retro_game_geometry {
                 base_width: dimensions.width,
@@ -241,37 +671,102 @@ impl Core for Ruffle {
                 aspect_ratio: (dimensions.width as f32) / (dimensions.height as f32),
             },
             timing: retro_system_timing {
-                fps: f64::from(movie.frame_rate()),
+                fps: frame_rate,
                 sample_rate: self.config.sample_rate as f64,
             },
         });
 
+        unsafe { frame_time::register(environ_cb, frame_rate) }?;
+        Self::report_audio_latency(environ_cb, self.config.sample_rate, frame_rate);
+
         let builder = PlayerBuilder::new()
             .with_movie(movie)
-            .with_ui(RetroUiBackend::new(self.environ_cb.clone()))
+            .with_ui(RetroUiBackend::new(self.environ_cb.clone(), self.config.clipboard))
             .with_log(RetroLogBackend::new())
-            .with_audio(RetroAudioBackend::new(2, self.config.sample_rate))
-            //.with_navigator(RetroNavigatorBackend::new())
+            .with_audio(RetroAudioBackend::new(
+                self.config.audio_channels,
+                self.config.sample_rate,
+                self.config.sample_rate,
+                frame_rate,
+                self.config.audio_precision,
+                self.config.resampler_quality,
+            ))
             .with_video(SoftwareVideoBackend::new())
             .with_autoplay(self.config.autoplay)
             .with_letterbox(self.config.letterbox)
             .with_max_execution_duration(self.config.max_execution_duration)
             .with_warn_on_unsupported_content(self.config.warn_on_unsupported_content)
-            .with_viewport_dimensions(dimensions.width, dimensions.height, dimensions.scale_factor)
+            .with_viewport_dimensions(viewport.width, viewport.height, viewport.scale_factor)
             .with_fullscreen(true)
+            .with_frame_rate(frame_rate_override)
             .with_load_behavior(self.config.load_behavior)
             .with_spoofed_url(self.config.spoofed_url.clone());
 
+        // Forced, so the movie can't move itself back with Stage.align
+        let builder = match self.config.stage_align {
+            Some(align) => builder.with_align(align, true),
+            None => builder,
+        };
+
+        self.benchmark = self
+            .config
+            .benchmark_frames
+            .map(|frames| Benchmark::new(frames, self.config.benchmark_exit));
+
+        self.frame_pacer = self
+            .av_info
+            .as_ref()
+            .and_then(Self::frame_time_us)
+            .filter(|_| self.config.frame_pacing)
+            .map(FramePacer::new);
+
         let save_directory = unsafe { get_save_directory(environ_cb) };
+        let cache = match &save_directory {
+            Ok(Some(base_path)) if self.config.http_cache && self.vfs.get().is_some() => {
+                match RetroVfsStorageBackend::with_layout(base_path, HTTP_CACHE_DIRECTORY, "cache", self.vfs.clone()) {
+                    Ok(storage) => Some(HttpCache::new(storage)),
+                    Err(e) => {
+                        warn!("Can't cache downloads in {base_path:?}: {e}");
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        // Settings the navigator reads are fixed from here on, like the rest of the player's
+        let executor = RetroExecutor::new();
+        let builder = builder.with_navigator(RetroNavigatorBackend::new(
+            movie_url,
+            executor.clone(),
+            None,
+            cache,
+            self.environ_cb.clone(),
+            self.vfs.clone(),
+            self.config.clone(),
+        ));
+        self.executor = Some(executor);
+
         let builder = match save_directory {
-            Ok(Some(base_path)) => builder.with_storage(RetroVfsStorageBackend::new(base_path, self.vfs.clone())?),
+            Ok(Some(base_path)) if self.vfs.get().is_some() => {
+                builder.with_storage(RetroVfsStorageBackend::new(base_path, self.vfs.clone())?)
+            }
             _ => builder.with_storage(MemoryStorageBackend::new()),
         };
 
-        // Renderer not initialized here, because we can't do so
-        // until the frontend calls on_hw_context_reset
-
-        self.player = Pending(builder.into());
+        self.player = if hw_render_enabled {
+            // Renderer not initialized here, because we can't do so
+            // until the frontend calls on_hw_context_reset
+            Pending(builder.into())
+        } else {
+            // No context reset is coming, so the fallback renderer has to be set up now
+            Active(
+                self.finalize_fallback_player(builder)
+                    .map_err(|e| anyhow::anyhow!("Failed to initialize fallback renderer: {e}"))?,
+            )
+        };
+        self.update_audio_dump();
+        self.preview_pending = !self.config.autoplay && self.config.paused_frame > 1;
 
         Ok(())
     }
@@ -281,76 +776,321 @@ impl Core for Ruffle {
         #[cfg(feature = "profiler")]
         profiling::scope!("retro_unload_game");
         debug!("Ruffle::on_unload_game()");
+        self.shut_down_player();
         self.player = Uninitialized;
+        self.executor = None;
+        self.content_device = None;
+        // The render backend is gone now, so none of the negotiated handles should outlive this content
+        unsafe { negotiation::forget_context() };
+        self.benchmark = None;
+        self.adaptive_quality = AdaptiveQuality::default();
+        self.frame_pacer = None;
+        self.frame_limiter = None;
+        self.audio_dump = None;
+        self.held_keys.clear();
+        self.queued_events.clear();
+        self.pause_requested = false;
+        self.end_watcher = EndWatcher::default();
+        self.preview_pending = false;
+        self.preview_shown = false;
+        self.background_color = None;
+        self.rejected_frame_rate = None;
+        self.av_info = None;
     }
 
     fn on_options_changed(&mut self, ctx: &mut OptionsChangedContext) {
         #[cfg(feature = "profiler")]
         profiling::scope!("Ruffle::on_options_changed");
-        self.config.autoplay = match ctx.get_variable("ruffle_autoplay") {
-            Ok(Some("true")) => true,
-            Ok(Some("false")) => false,
-            _ => defaults::AUTOPLAY,
+        // Doesn't touch the user's saved option values, so turning this off brings them all back
+        let use_defaults = parse::flag(ctx.get_variable("ruffle_use_defaults").unwrap_or(None), defaults::USE_DEFAULTS);
+        if use_defaults {
+            info!("Ignoring core options and using their defaults");
+        }
+        let get = |key| if use_defaults { Ok(None) } else { ctx.get_variable(key) };
+
+        self.config.autoplay = parse::flag(get("ruffle_autoplay").unwrap_or(None), defaults::AUTOPLAY);
+        self.config.paused_frame = parse::paused_frame(get("ruffle_paused_frame").unwrap_or(None));
+
+        self.config.end_behavior = match get("ruffle_end_behavior") {
+            Ok(Some("loop")) => EndBehavior::Loop,
+            Ok(Some("freeze")) => EndBehavior::Freeze,
+            Ok(Some("restart")) => EndBehavior::Restart,
+            Ok(Some("shutdown")) => EndBehavior::Shutdown,
+            _ => defaults::END_BEHAVIOR,
         };
 
-        self.config.letterbox = match ctx.get_variable("ruffle_letterbox") {
+        self.config.letterbox = match get("ruffle_letterbox") {
             Ok(Some("off")) => Letterbox::Off,
             Ok(Some("fullscreen")) => Letterbox::Fullscreen,
             Ok(Some("on")) => Letterbox::On,
             _ => defaults::LETTERBOX,
         }; // TODO: Should I reset the driver if this changed?
 
-        self.config.max_execution_duration = ctx
-            .get_variable("ruffle_max_execution_duration")
-            .unwrap_or(None)
-            .and_then(|s: &str| s.parse::<u64>().ok())
-            .map(Duration::from_secs)
-            .unwrap_or(defaults::MAX_EXECUTION_DURATION);
+        self.config.letterbox_color = parse::letterbox_color(get("ruffle_letterbox_color").unwrap_or(None));
+
+        self.config.aspect_ratio_mode = match get("ruffle_aspect_ratio_mode") {
+            Ok(Some("geometry")) => AspectRatioMode::Geometry,
+            Ok(Some("pad_4_3")) => AspectRatioMode::Pad4x3,
+            Ok(Some("pad_16_9")) => AspectRatioMode::Pad16x9,
+            _ => defaults::ASPECT_RATIO_MODE,
+        };
+
+        self.config.post_process = match get("ruffle_post_process") {
+            Ok(Some("off")) => PostProcessPreset::Off,
+            Ok(Some("scanlines")) => PostProcessPreset::Scanlines,
+            Ok(Some("crt")) => PostProcessPreset::Crt,
+            _ => defaults::POST_PROCESS,
+        };
 
-        self.config.msaa = ctx
-            .get_variable("ruffle_msaa")
-            .unwrap_or(None)
-            .and_then(|s: &str| s.parse::<u8>().ok())
-            .unwrap_or(defaults::MSAA);
+        self.config.overlay = match get("ruffle_overlay") {
+            Ok(Some("off")) => GuideOverlay::Off,
+            Ok(Some("safe_area")) => GuideOverlay::SafeArea,
+            Ok(Some("thirds")) => GuideOverlay::Thirds,
+            Ok(Some("grid")) => GuideOverlay::Grid,
+            _ => defaults::OVERLAY,
+        };
 
-        self.config.warn_on_unsupported_content = match ctx.get_variable("ruffle_warn_on_unsupported_content") {
-            Ok(Some("true")) => true,
-            Ok(Some("false")) => false,
-            _ => defaults::WARN_ON_UNSUPPORTED_CONTENT,
+        self.config.stage_align = match get("ruffle_stage_align") {
+            Ok(Some("movie")) => None,
+            Ok(Some("center")) => Some(StageAlign::empty()),
+            Ok(Some("top_left")) => Some(StageAlign::TOP | StageAlign::LEFT),
+            Ok(Some("top")) => Some(StageAlign::TOP),
+            Ok(Some("top_right")) => Some(StageAlign::TOP | StageAlign::RIGHT),
+            Ok(Some("left")) => Some(StageAlign::LEFT),
+            Ok(Some("right")) => Some(StageAlign::RIGHT),
+            Ok(Some("bottom_left")) => Some(StageAlign::BOTTOM | StageAlign::LEFT),
+            Ok(Some("bottom")) => Some(StageAlign::BOTTOM),
+            Ok(Some("bottom_right")) => Some(StageAlign::BOTTOM | StageAlign::RIGHT),
+            _ => defaults::STAGE_ALIGN,
         };
 
-        self.config.file_access_policy = match ctx.get_variable("ruffle_file_access_policy") {
+        self.config.max_execution_duration =
+            parse::max_execution_duration(get("ruffle_max_execution_duration").unwrap_or(None));
+
+        self.config.msaa = parse::msaa(get("ruffle_msaa").unwrap_or(None));
+
+        self.config.text_scale = parse::text_scale(get("ruffle_text_scale").unwrap_or(None));
+
+        self.config.render_scale = parse::render_scale(get("ruffle_render_scale").unwrap_or(None));
+
+        self.config.warn_on_unsupported_content = parse::flag(
+            get("ruffle_warn_on_unsupported_content").unwrap_or(None),
+            defaults::WARN_ON_UNSUPPORTED_CONTENT,
+        );
+
+        self.config.file_access_policy = match get("ruffle_file_access_policy") {
             Ok(Some("never")) => FileAccessPolicy::Never,
             Ok(Some("notify")) => FileAccessPolicy::Notify,
             Ok(Some("always")) => FileAccessPolicy::Always,
             _ => defaults::FILE_ACCESS_POLICY,
         };
 
-        self.config.web_browser_access = match ctx.get_variable("ruffle_web_browser_access") {
+        self.config.web_browser_access = match get("ruffle_web_browser_access") {
             Ok(Some("off")) => WebBrowserAccess::Ignore,
             Ok(Some("off-notify")) => WebBrowserAccess::Notify,
             Ok(Some("external")) => WebBrowserAccess::OpenInBrowser,
             _ => defaults::WEB_BROWSER_ACCESS,
         };
 
-        self.config.sample_rate = ctx
-            .get_variable("ruffle_audio_sample_rate")
-            .unwrap_or(None)
-            .and_then(|s: &str| s.parse::<u32>().ok())
-            .unwrap_or(defaults::SAMPLE_RATE);
+        self.config.power_save = parse::flag(get("ruffle_power_save").unwrap_or(None), defaults::POWER_SAVE);
+
+        self.config.benchmark_frames = parse::benchmark_frames(get("ruffle_benchmark").unwrap_or(None));
+
+        self.config.benchmark_exit = parse::flag(
+            get("ruffle_benchmark_exit").unwrap_or(None),
+            defaults::BENCHMARK_EXIT,
+        );
+
+        self.config.external_interface = match get("ruffle_external_interface") {
+            Ok(Some("off")) => ExternalInterfaceMode::Disabled,
+            Ok(Some("log")) => ExternalInterfaceMode::Log,
+            Ok(Some("actions")) => ExternalInterfaceMode::Actions,
+            _ => defaults::EXTERNAL_INTERFACE,
+        };
+
+        self.config.fallback_backend = match get("ruffle_fallback_backend") {
+            Ok(Some("auto")) => FallbackBackend::Auto,
+            Ok(Some("gl")) => FallbackBackend::Gl,
+            Ok(Some("vulkan")) => FallbackBackend::Vulkan,
+            _ => defaults::FALLBACK_BACKEND,
+        };
+
+        self.config.max_bitmap_memory = parse::max_bitmap_memory(get("ruffle_max_bitmap_memory").unwrap_or(None));
+
+        self.config.vulkan_frames = parse::vulkan_frames(get("ruffle_vulkan_frames").unwrap_or(None));
+
+        self.config.sample_rate = parse::sample_rate(get("ruffle_audio_sample_rate").unwrap_or(None));
+        if self.active_sample_rate().map_or(false, |rate| rate != self.config.sample_rate) {
+            self.update_sample_rate(self.config.sample_rate);
+        }
+
+        self.config.volume = parse::volume(get("ruffle_audio_volume").unwrap_or(None));
+
+        self.config.audio_precision = match get("ruffle_audio_precision") {
+            Ok(Some("int16")) => AudioPrecision::Int16,
+            Ok(Some("float")) => AudioPrecision::Float,
+            _ => defaults::AUDIO_PRECISION,
+        };
+
+        self.config.audio_channels = match get("ruffle_audio_channels") {
+            Ok(Some("stereo")) => AudioChannels::Stereo,
+            Ok(Some("mono")) => AudioChannels::Mono,
+            _ => defaults::AUDIO_CHANNELS,
+        };
+
+        self.config.resampler_quality = match get("ruffle_resampler_quality") {
+            Ok(Some("nearest")) => ResamplerQuality::Nearest,
+            Ok(Some("linear")) => ResamplerQuality::Linear,
+            Ok(Some("sinc")) => ResamplerQuality::Sinc,
+            _ => defaults::RESAMPLER_QUALITY,
+        };
+
+        self.config.audio_mute = parse::flag(get("ruffle_audio_mute").unwrap_or(None), defaults::AUDIO_MUTE);
+
+        self.config.keyboard_passthrough = match get("ruffle_keyboard_passthrough") {
+            Ok(Some("all")) => KeyboardPassthrough::All,
+            Ok(Some("reserve_menu")) => KeyboardPassthrough::ReserveMenu,
+            Ok(Some("reserve_function_keys")) => KeyboardPassthrough::ReserveFunctionKeys,
+            _ => defaults::KEYBOARD_PASSTHROUGH,
+        };
+
+        self.config.pause_key = parse::flag(get("ruffle_pause_key").unwrap_or(None), defaults::PAUSE_KEY);
+
+        self.config.key_repeat_delay = parse::key_repeat_delay(get("ruffle_key_repeat_delay").unwrap_or(None));
+        self.config.key_repeat_interval = parse::key_repeat_interval(get("ruffle_key_repeat_rate").unwrap_or(None));
+        if self.config.key_repeat_delay.is_none() {
+            self.key_repeat.clear();
+        }
+
+        self.config.mouse_confine = parse::flag(get("ruffle_mouse_confine").unwrap_or(None), defaults::MOUSE_CONFINE);
+        self.config.software_cursor = parse::flag(
+            get("ruffle_software_cursor").unwrap_or(None),
+            defaults::SOFTWARE_CURSOR,
+        );
+
+        self.config.analog_cursor = match get("ruffle_analog_cursor") {
+            Ok(Some("off")) => AnalogCursor::Off,
+            Ok(Some("linear")) => AnalogCursor::Linear,
+            Ok(Some("accelerated")) => AnalogCursor::Accelerated,
+            _ => defaults::ANALOG_CURSOR,
+        };
+
+        self.config.mouse_axes = match get("ruffle_mouse_axis") {
+            Ok(Some("normal")) => MouseAxes::Normal,
+            Ok(Some("swap")) => MouseAxes::Swapped,
+            Ok(Some("invert_x")) => MouseAxes::InvertX,
+            Ok(Some("invert_y")) => MouseAxes::InvertY,
+            Ok(Some("invert_both")) => MouseAxes::InvertBoth,
+            _ => defaults::MOUSE_AXES,
+        };
+
+        self.config.mouse_sensitivity = parse::mouse_sensitivity(get("ruffle_mouse_sensitivity").unwrap_or(None));
+
+        self.config.wheel_invert = parse::flag(get("ruffle_wheel_invert").unwrap_or(None), defaults::WHEEL_INVERT);
+
+        self.config.auto_device = parse::flag(get("ruffle_auto_device").unwrap_or(None), defaults::AUTO_DEVICE);
+
+        self.config.render_diagnostics = parse::flag(
+            get("ruffle_render_diagnostics").unwrap_or(None),
+            defaults::RENDER_DIAGNOSTICS,
+        );
+
+        self.config.vulkan_validation = parse::flag(
+            get("ruffle_vulkan_validation").unwrap_or(None),
+            defaults::VULKAN_VALIDATION,
+        );
+
+        self.config.audio_visualizer = parse::flag(
+            get("ruffle_audio_visualizer").unwrap_or(None),
+            defaults::AUDIO_VISUALIZER,
+        );
+
+        self.config.right_click = match get("ruffle_right_click") {
+            Ok(Some("context-menu")) => RightClickMode::ContextMenu,
+            Ok(Some("input")) => RightClickMode::Input,
+            _ => defaults::RIGHT_CLICK,
+        };
+
+        self.config.frame_pacing = parse::flag(get("ruffle_frame_pacing").unwrap_or(None), defaults::FRAME_PACING);
+
+        self.config.max_fps = parse::max_fps(get("ruffle_max_fps").unwrap_or(None));
+        if self.frame_limiter.as_ref().map(FrameLimiter::max_fps) != self.config.max_fps {
+            self.frame_limiter = self.config.max_fps.map(FrameLimiter::new);
+        }
+
+        self.config.adaptive_quality = parse::flag(
+            get("ruffle_adaptive_quality").unwrap_or(None),
+            defaults::ADAPTIVE_QUALITY,
+        );
+
+        self.config.clipboard = parse::flag(get("ruffle_clipboard").unwrap_or(None), defaults::CLIPBOARD);
+
+        self.config.allow_quit = parse::flag(get("ruffle_allow_quit").unwrap_or(None), defaults::ALLOW_QUIT);
+
+        self.config.network_access = parse::flag(
+            get("ruffle_network_access").unwrap_or(None),
+            defaults::NETWORK_ACCESS,
+        );
+
+        self.config.upgrade_to_https = parse::flag(
+            get("ruffle_upgrade_to_https").unwrap_or(None),
+            defaults::UPGRADE_TO_HTTPS,
+        );
+
+        self.config.http_cache = parse::flag(get("ruffle_http_cache").unwrap_or(None), defaults::HTTP_CACHE);
 
-        self.config.load_behavior = match ctx.get_variable("ruffle_load_behavior") {
+        self.config.max_requests = parse::max_requests(get("ruffle_max_requests").unwrap_or(None));
+
+        self.config.load_behavior = match get("ruffle_load_behavior") {
             Ok(Some("streaming")) => LoadBehavior::Streaming,
             Ok(Some("blocking")) => LoadBehavior::Blocking,
             Ok(Some("delayed")) => LoadBehavior::Delayed,
             _ => defaults::LOAD_BEHAVIOR,
         };
 
+        let show_render_status = parse::flag(
+            get("ruffle_show_render_status").unwrap_or(None),
+            defaults::SHOW_RENDER_STATUS,
+        );
+        let render_status_requested = show_render_status && !self.config.show_render_status;
+        self.config.show_render_status = show_render_status;
+
+        self.config.audio_dump = parse::flag(get("ruffle_audio_dump").unwrap_or(None), defaults::AUDIO_DUMP);
+        self.update_audio_dump();
+
         if let Active(player) = &self.player {
             let mut player = player.lock().unwrap();
 
+            if render_status_requested {
+                let status = Self::render_status(&mut player, self.config.msaa);
+                Self::notify_info(&GenericContext::from(ctx), &status, RENDER_STATUS_DURATION, RENDER_STATUS_PRIORITY);
+            }
+
             player.set_letterbox(self.config.letterbox); // TODO: What if old letterbox == new letterbox?
             player.set_max_execution_duration(self.config.max_execution_duration);
+            player.audio_mut().set_volume(self.config.volume);
+            let audio = player
+                .audio_mut()
+                .downcast_mut::<RetroAudioBackend>()
+                .expect("Unexpected AudioBackend implementation");
+            audio.set_channels(self.config.audio_channels);
+            audio.set_resampler_quality(self.config.resampler_quality);
+            audio.set_muted(self.config.audio_mute);
+
+            if let Some(renderer) = player.renderer_mut().downcast_mut::<VulkanWgpuRenderBackend>() {
+                renderer.set_frame_queue_depth(self.config.vulkan_frames);
+                renderer.set_max_bitmap_memory(self.config.max_bitmap_memory);
+                renderer.set_post_process(self.config.post_process);
+                renderer.set_overlay(self.config.overlay);
+            } else if let Some(renderer) = player.renderer_mut().downcast_mut::<OpenGlWgpuRenderBackend>() {
+                renderer.set_max_bitmap_memory(self.config.max_bitmap_memory);
+                renderer.set_post_process(self.config.post_process);
+                renderer.set_overlay(self.config.overlay);
+            } else if let Some(renderer) = player.renderer_mut().downcast_mut::<SoftwareWgpuRenderBackend>() {
+                renderer.set_max_bitmap_memory(self.config.max_bitmap_memory);
+            }
+
+            Self::apply_letterbox_color(&mut player, &self.config);
         }
     }
 
@@ -358,6 +1098,32 @@ impl Core for Ruffle {
         debug!("retro_keyboard_callback_fn(down = {down}, keycode = {keycode:?}, character = {character}, key_modifiers = {key_modifiers:?})");
         #[cfg(feature = "profiler")]
         profiling::scope!("retro_keyboard_callback::callback");
+        if !input::accepts_keyboard(self.port_device) {
+            return;
+        }
+
+        if input::reserved_for_frontend(keycode, self.config.keyboard_passthrough) {
+            return;
+        }
+
+        if self.config.pause_key && keycode == retro_key::RETROK_PAUSE {
+            // Toggled on the next frame, since the player can't be locked from here
+            self.pause_requested |= down;
+            return;
+        }
+
+        if down {
+            if !self.held_keys.contains(&keycode) {
+                self.held_keys.push(keycode);
+            }
+            if let Some(delay) = self.config.key_repeat_delay {
+                self.key_repeat.press(keycode, Instant::now(), delay);
+            }
+        } else {
+            self.held_keys.retain(|&k| k != keycode);
+            self.key_repeat.release(keycode);
+        }
+
         let event = match (down, keycode) {
             (true, keycode) => PlayerEvent::KeyDown {
                 key_code: util::keyboard::to_key_code(keycode),
@@ -375,14 +1141,19 @@ impl Core for Ruffle {
     fn on_write_audio(&mut self, ctx: &mut AudioContext) {
         #[cfg(feature = "profiler")]
         profiling::scope!("retro_audio_callback::callback");
+        // Only called if the audio callback is enabled, which it isn't; on_run sends audio instead.
+        // Still mixes properly, in case it ever is (at which point on_run must stop sending audio)
         if let Active(player) = &self.player {
             let mut player = player.lock().unwrap();
-            let player = player.deref_mut();
-
-            let audio = player.audio_mut();
-
-            let resampled_samples: [i16; 2048] = [0; 2048];
-            ctx.batch_audio_samples(&resampled_samples);
+            let audio = player
+                .audio_mut()
+                .downcast_mut::<RetroAudioBackend>()
+                .expect("Unexpected AudioBackend implementation");
+
+            audio.mix_frame(None);
+            if let Some(samples) = audio.current_samples() {
+                ctx.batch_audio_samples(samples);
+            }
         }
     }
 
@@ -393,24 +1164,41 @@ impl Core for Ruffle {
             let mut player = player.lock().unwrap();
             let player = player.deref_mut();
 
-            if enabled {
-                player.audio_mut().play();
-            } else {
-                player.audio_mut().pause();
-            }
+            // Not play() and pause(), which Ruffle uses for its own pausing; this mustn't unpause a paused movie
+            player
+                .audio_mut()
+                .downcast_mut::<RetroAudioBackend>()
+                .expect("Unexpected AudioBackend implementation")
+                .set_suspended(!enabled);
         } else {
             warn!("on_audio_set_state({enabled}) called before player was ready");
         }
     }
 
+    fn on_audio_buffer_status(&mut self, active: bool, occupancy: u32, underrun_likely: bool) {
+        #[cfg(feature = "profiler")]
+        profiling::scope!("retro_audio_buffer_status_callback");
+        if !active || !self.config.adaptive_quality {
+            return;
+        }
+
+        let quality = self.adaptive_quality.update(occupancy, underrun_likely);
+        if let (Active(player), Some(quality)) = (&self.player, quality) {
+            info!("Audio buffer at {occupancy}% (underrun likely: {underrun_likely}), using {quality:?} quality");
+            player.lock().unwrap().set_quality(quality);
+        }
+    }
+
     fn on_hw_context_reset(&mut self, context: &mut GenericContext) {
         debug!("Core::on_hw_context_reset()");
         #[cfg(feature = "profiler")]
         profiling::scope!("retro_hw_render_callback::context_reset");
+        self.release_held_keys();
         match &self.player {
             Active(_) => {
                 // Game is already running
-                self.player = Exiting;
+                self.shut_down_player();
+                self.player = Exiting(ExitReason::ContextLost);
                 context.shutdown();
             }
             Pending(builder) => {
@@ -419,6 +1207,8 @@ impl Core for Ruffle {
                     // We take ownership of the builder, then throw it out after the player is built
                     Ok(player) => {
                         info!("Initialized render backend and finalized player");
+                        let status = Self::render_status(&mut player.lock().unwrap(), self.config.msaa);
+                        Self::notify_info(context, &status, RENDER_STATUS_DURATION, RENDER_STATUS_PRIORITY);
                         Active(player)
                     }
                     Err(error) => {
@@ -430,7 +1220,7 @@ impl Core for Ruffle {
             Uninitialized => {
                 warn!("Resetting hardware context before core is ready");
             }
-            Exiting => {
+            Exiting(_) => {
                 warn!("Resetting hardware context after a fatal error");
             }
         };
@@ -442,7 +1232,8 @@ impl Core for Ruffle {
         profiling::scope!("retro_hw_render_callback::context_destroy");
         match &self.player {
             Active(_) => {
-                self.player = Exiting;
+                self.shut_down_player();
+                self.player = Exiting(ExitReason::ContextLost);
                 ctx.shutdown();
             }
             _ => {
@@ -456,11 +1247,71 @@ impl Core for Ruffle {
     }
 }
 
+const VULKAN_UNAVAILABLE_MESSAGE: &str =
+    "Ruffle couldn't use the frontend's Vulkan driver, so it's rendering in software.\nTry the glcore driver instead.";
+const VULKAN_UNAVAILABLE_PRIORITY: u32 = 32;
+const VULKAN_UNAVAILABLE_DURATION: u32 = 5000;
+
 const CONTEXT_LOST_MESSAGE: &'static str =
     "Hardware context lost, and Ruffle can't reinitialize it.\nPlease reload the game.";
 const CONTEXT_LOST_PRIORITY: u32 = 32;
 const CONTEXT_LOST_DURATION: u32 = 3000;
 
+/// Loaded from the frontend's system directory when the core is started without content.
+const HOME_MOVIE_PATH: &str = "ruffle/home.swf";
+const RENDER_DIAGNOSTICS_PATH: &str = "ruffle/render_diagnostics.txt";
+const AUDIO_DUMP_DIRECTORY: &str = "ruffle";
+const AUDIO_DUMP_CHANNELS: u16 = 2;
+const HTTP_CACHE_DIRECTORY: &str = "HttpCache";
+const NO_GAME_MESSAGE: &'static str =
+    "No movie was loaded. Load a .swf file,\nor place a launcher movie at <system directory>/ruffle/home.swf.";
+const NO_GAME_PRIORITY: u32 = 32;
+const NO_GAME_DURATION: u32 = 5000;
+
+const PROTECTED_SWF_MESSAGE: &'static str =
+    "This movie is encrypted or protected, so Ruffle can't play it.\nTry an unprotected copy of the original file.";
+const PROTECTED_SWF_PRIORITY: u32 = 32;
+const PROTECTED_SWF_DURATION: u32 = 5000;
+
+const DOWNLOAD_FAILED_PRIORITY: u32 = 32;
+const DOWNLOAD_FAILED_DURATION: u32 = 5000;
+
+const NEW_SWF_VERSION_PRIORITY: u32 = 0;
+const NEW_SWF_VERSION_DURATION: u32 = 5000;
+
+const UNSUPPORTED_VIDEO_PRIORITY: u32 = 0;
+const UNSUPPORTED_VIDEO_DURATION: u32 = 5000;
+
+const EXIT_REQUESTED_MESSAGE: &'static str = "Ruffle is closing as requested.";
+const EXIT_REQUESTED_PRIORITY: u32 = 0;
+const EXIT_REQUESTED_DURATION: u32 = 2000;
+
+const BENCHMARK_PRIORITY: u32 = 0;
+const BENCHMARK_DURATION: u32 = 10000;
+
+const PAUSED_MESSAGE: &str = "Paused";
+const RESUMED_MESSAGE: &str = "Resumed";
+const PAUSE_PRIORITY: u32 = 0;
+const PAUSE_DURATION: u32 = 1000;
+
+const CONTEXT_MENU_PRIORITY: u32 = 0;
+const CONTEXT_MENU_DURATION: u32 = 3000;
+
+const BACKGROUND_COLOR_PRIORITY: u32 = 0;
+const BACKGROUND_COLOR_DURATION: u32 = 3000;
+
+const RENDER_STATUS_PRIORITY: u32 = 0;
+const RENDER_STATUS_DURATION: u32 = 3000;
+
+/// Frame rate used in place of a movie's own if it's zero or otherwise invalid.
+const MIN_FRAME_RATE: f64 = 1.0;
+
+/// How much of an idle frame's budget to sleep through when power saving is enabled.
+const POWER_SAVE_IDLE_FRACTION: f64 = 0.5;
+
+/// Gaps between frames longer than this many frames are treated as the core having been suspended.
+const MAX_CATCH_UP_FRAMES: i64 = 4;
+
 impl Ruffle {
     fn finalize_player(
         &self,
@@ -487,40 +1338,373 @@ impl Ruffle {
             | RETRO_HW_CONTEXT_OPENGLES3
             | RETRO_HW_CONTEXT_OPENGL_CORE
             | RETRO_HW_CONTEXT_OPENGLES_VERSION => {
-                builder.with_renderer(block_on(OpenGlWgpuRenderBackend::new(&hw_render_callback, &av_info.geometry))?)
+                let mut renderer = block_on(OpenGlWgpuRenderBackend::new(
+                    &hw_render_callback,
+                    &av_info.geometry,
+                    self.config.max_bitmap_memory,
+                    self.config.post_process,
+                ))?;
+                renderer.set_overlay(self.config.overlay);
+                if self.config.render_diagnostics {
+                    self.write_render_diagnostics(&renderer.diagnostics());
+                }
+                builder.with_renderer(renderer)
             }
             RETRO_HW_CONTEXT_VULKAN => {
-                let render_interface = unsafe { ctx.get_hw_render_interface_vulkan()? };
-                builder.with_renderer(VulkanWgpuRenderBackend::new(&av_info.geometry, &render_interface)?)
+                let render_interface = match unsafe { ctx.get_hw_render_interface_vulkan() } {
+                    Ok(render_interface) => render_interface,
+                    Err(error) => {
+                        // The frontend already committed to Vulkan, but it still accepts software frames
+                        warn!("No Vulkan render interface from the frontend, using the fallback renderer: {error}");
+                        Self::notify_error(
+                            ctx,
+                            VULKAN_UNAVAILABLE_MESSAGE,
+                            VULKAN_UNAVAILABLE_DURATION,
+                            VULKAN_UNAVAILABLE_PRIORITY,
+                        );
+                        return self.finalize_fallback_player(builder);
+                    }
+                };
+                let mut renderer = VulkanWgpuRenderBackend::new(
+                    &av_info.geometry,
+                    &render_interface,
+                    self.config.vulkan_frames,
+                    self.config.max_bitmap_memory,
+                    self.config.post_process,
+                )?;
+                renderer.set_overlay(self.config.overlay);
+                if self.config.render_diagnostics {
+                    self.write_render_diagnostics(&renderer.diagnostics());
+                }
+                builder.with_renderer(renderer)
             }
             other => Err(UnsupportedHardwareContext(other))?,
         };
 
-        Ok(builder.build())
+        Ok(self.finish_player(builder.build()))
+    }
+
+    fn write_render_diagnostics(&self, report: &str) {
+        let path = match unsafe { get_system_directory(self.environ_cb.get()) } {
+            Ok(Some(system_directory)) => system_directory.join(RENDER_DIAGNOSTICS_PATH),
+            _ => {
+                warn!("No system directory available, can't write render diagnostics");
+                return;
+            }
+        };
+
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, report));
+        match result {
+            Ok(()) => info!("Wrote render diagnostics to {path:?}"),
+            Err(e) => warn!("Failed to write render diagnostics to {path:?}: {e}"),
+        }
+    }
+
+    /// The device whose controls are described to the frontend: the one chosen for port 1,
+    /// or if none of ours was chosen, the one the movie seems to be built for.
+    /// Input from both keyboard and mouse is still accepted until the user chooses.
+    fn descriptor_device(&self) -> Option<u32> {
+        if input::is_offered(self.port_device) {
+            self.port_device
+        } else {
+            self.content_device.or(self.port_device)
+        }
+    }
+
+    /// Queues a KeyUp for every key that's still held, so nothing stays pressed
+    /// if the frontend stops sending keyboard events.
+    fn release_held_keys(&mut self) {
+        self.key_repeat.clear();
+        for keycode in self.held_keys.drain(..) {
+            self.queued_events.push_back(PlayerEvent::KeyUp {
+                key_code: util::keyboard::to_key_code(keycode),
+                key_char: None,
+            });
+        }
+    }
+
+    fn finalize_fallback_player(&self, builder: PlayerBuilder) -> Result<Arc<Mutex<Player>>, Box<dyn Error>> {
+        #[cfg(feature = "profiler")]
+        profiling::scope!("Ruffle::finalize_fallback_player");
+        let av_info = &self
+            .av_info
+            .expect("av_info should've been initialized in on_load_game");
+
+        let renderer = SoftwareWgpuRenderBackend::new(
+            &av_info.geometry,
+            self.config.fallback_backend.backends(),
+            self.config.max_bitmap_memory,
+        )?;
+
+        Ok(self.finish_player(builder.with_renderer(renderer).build()))
+    }
+
+    fn finish_player(&self, player: Arc<Mutex<Player>>) -> Arc<Mutex<Player>> {
+        if self.config.external_interface != ExternalInterfaceMode::Disabled || self.config.allow_quit {
+            let provider = RetroExternalInterfaceProvider::new(
+                self.config.external_interface,
+                self.config.allow_quit,
+                self.environ_cb.clone(),
+            );
+            player.lock().unwrap().add_external_interface(Box::new(provider));
+        }
+
+        Self::apply_letterbox_color(&mut player.lock().unwrap(), &self.config);
+        player.lock().unwrap().audio_mut().set_volume(self.config.volume);
+        player
+            .lock()
+            .unwrap()
+            .audio_mut()
+            .downcast_mut::<RetroAudioBackend>()
+            .expect("Unexpected AudioBackend implementation")
+            .set_muted(self.config.audio_mute);
+
+        // We start out fullscreen because the frontend owns the whole window, not because the movie asked;
+        // only a movie's own request for plain fullscreen should restrict keyboard input
+        player.lock().unwrap().mutate_with_update_context(|context| {
+            context
+                .stage
+                .set_display_state(context, StageDisplayState::FullScreenInteractive);
+        });
+
+        player
+    }
+
+    /// Logs and shows the stage's background color whenever it changes, usually once the first frame sets it.
+    /// libretro has no way to tell the frontend what color to draw its borders, so this lets the user match them
+    /// by hand; `ruffle_letterbox_color` can still match the core's own letterbox bars to it.
+    fn report_background_color(player: &mut Player, reported: &mut Option<[u8; 3]>, ctx: &mut RunContext) {
+        let color = player
+            .mutate_with_update_context(|context| context.stage.background_color())
+            .map(|color| [color.r, color.g, color.b]);
+
+        if color.is_some() && color != *reported {
+            let [r, g, b] = color.unwrap_or_default();
+            let message = format!("Stage background color is #{r:02x}{g:02x}{b:02x}");
+            info!("{message}");
+            *reported = color;
+
+            let ctx = GenericContext::from(&mut *ctx);
+            Self::notify_info(&ctx, &message, BACKGROUND_COLOR_DURATION, BACKGROUND_COLOR_PRIORITY);
+        }
+    }
+
+    /// The rate the loaded content's audio is mixed at, which may differ from `Config::sample_rate`
+    /// if that was changed after loading.
+    fn active_sample_rate(&self) -> Option<u32> {
+        self.av_info.as_ref().map(|av_info| av_info.timing.sample_rate as u32)
+    }
+
+    /// Asks the frontend to buffer enough audio for a couple of frames at `frame_rate`.
+    fn report_audio_latency(environ_cb: retro_environment_t, sample_rate: u32, frame_rate: f64) {
+        if let Some(latency) = RetroAudioBackend::minimum_latency(sample_rate, frame_rate) {
+            let latency = (latency.as_secs_f64() * 1000.0).ceil() as u32;
+            if let Err(e) = unsafe { environment::set_minimum_audio_latency(environ_cb, latency) } {
+                warn!("RETRO_ENVIRONMENT_SET_MINIMUM_AUDIO_LATENCY({latency}) failed: {e}");
+            }
+        }
+    }
+
+    /// Switches the loaded content's audio to a new output rate.
+    /// Ruffle's mixer can't change rates once it's created, so its output is resampled instead.
+    fn update_sample_rate(&mut self, sample_rate: u32) {
+        let mut av_info = match self.av_info {
+            Some(av_info) => av_info,
+            None => return,
+        };
+
+        info!("Changing the sample rate from {} to {sample_rate}", av_info.timing.sample_rate);
+        av_info.timing.sample_rate = f64::from(sample_rate);
+
+        // Only switch once the frontend agrees, or it would play our audio at the wrong speed
+        let environ_cb = self.environ_cb.get();
+        if let Err(e) = unsafe { environment::set_system_av_info(environ_cb, av_info) } {
+            warn!("RETRO_ENVIRONMENT_SET_SYSTEM_AV_INFO failed, keeping the old sample rate: {e}");
+            return;
+        }
+        self.av_info = Some(av_info);
+
+        if let Active(player) = &self.player {
+            player
+                .lock()
+                .unwrap()
+                .audio_mut()
+                .downcast_mut::<RetroAudioBackend>()
+                .expect("Unexpected AudioBackend implementation")
+                .set_output_sample_rate(sample_rate);
+        }
+
+        Self::report_audio_latency(environ_cb, sample_rate, av_info.timing.fps);
+
+        // The old file's header says the old rate
+        if self.audio_dump.take().is_some() {
+            self.update_audio_dump();
+        }
+    }
+
+    /// Follows a movie that changed its frame rate after loading, so frame times and audio pacing match it.
+    fn update_frame_rate(&mut self, frame_rate: f64) {
+        let mut av_info = match self.av_info {
+            Some(av_info) => av_info,
+            None => return,
+        };
+
+        info!("Movie changed its frame rate from {} to {frame_rate}", av_info.timing.fps);
+        av_info.timing.fps = frame_rate;
+
+        // Only switch once the frontend agrees, or frame times and audio would be paced for a rate it isn't using
+        let environ_cb = self.environ_cb.get();
+        if let Err(e) = unsafe { environment::set_system_av_info(environ_cb, av_info) } {
+            warn!("RETRO_ENVIRONMENT_SET_SYSTEM_AV_INFO failed, keeping the old frame rate: {e}");
+            self.rejected_frame_rate = Some(frame_rate);
+            return;
+        }
+        self.av_info = Some(av_info);
+        self.rejected_frame_rate = None;
+
+        if let Err(e) = unsafe { frame_time::register(environ_cb, frame_rate) } {
+            warn!("RETRO_ENVIRONMENT_SET_FRAME_TIME_CALLBACK failed: {e}");
+        }
+
+        if let Active(player) = &self.player {
+            player
+                .lock()
+                .unwrap()
+                .audio_mut()
+                .downcast_mut::<RetroAudioBackend>()
+                .expect("Unexpected AudioBackend implementation")
+                .set_output_frame_rate(frame_rate);
+        }
+
+        Self::report_audio_latency(environ_cb, av_info.timing.sample_rate as u32, frame_rate);
+
+        if self.frame_pacer.is_some() {
+            self.frame_pacer = Self::frame_time_us(&av_info).map(FramePacer::new);
+        }
+    }
+
+    /// Tells the frontend which of its optional features this core supports, so it can show or hide the matching UI.
+    /// Must be called from retro_load_game. Update this as capabilities land;
+    /// savestates, for instance, will also need RETRO_ENVIRONMENT_SET_SERIALIZATION_QUIRKS.
+    fn report_capabilities(ctx: &GenericContext) {
+        let environ_cb = unsafe { *ctx.environment_callback() };
+
+        // Ruffle has no memory map for RetroAchievements to inspect
+        if let Err(e) = unsafe { environment::set_support_achievements(environ_cb, false) } {
+            debug!("RETRO_ENVIRONMENT_SET_SUPPORT_ACHIEVEMENTS failed: {e}");
+        }
+    }
+
+    /// Tells the renderer where the letterbox bars are, so it can recolor them.
+    /// Assumes the stage is scaled to fit the viewport, which is when Ruffle letterboxes it.
+    fn apply_letterbox_color(player: &mut Player, config: &Config) {
+        let viewport = player.renderer().viewport_dimensions();
+        let (movie_width, movie_height) = (f64::from(player.movie_width()), f64::from(player.movie_height()));
+        let stage_rect = (config.letterbox != Letterbox::Off && movie_width > 0.0 && movie_height > 0.0).then(|| {
+            let (viewport_width, viewport_height) = (f64::from(viewport.width), f64::from(viewport.height));
+            let scale = (viewport_width / movie_width).min(viewport_height / movie_height);
+            let (width, height) = (movie_width * scale, movie_height * scale);
+            let align = config.stage_align.unwrap_or_else(StageAlign::empty);
+            let x = match (align.contains(StageAlign::LEFT), align.contains(StageAlign::RIGHT)) {
+                (true, false) => 0.0,
+                (false, true) => viewport_width - width,
+                _ => (viewport_width - width) / 2.0,
+            };
+            let y = match (align.contains(StageAlign::TOP), align.contains(StageAlign::BOTTOM)) {
+                (true, false) => 0.0,
+                (false, true) => viewport_height - height,
+                _ => (viewport_height - height) / 2.0,
+            };
+            [x as f32, y as f32, width as f32, height as f32]
+        });
+
+        if let Some(renderer) = player.renderer_mut().downcast_mut::<VulkanWgpuRenderBackend>() {
+            renderer.set_letterbox(config.letterbox_color, stage_rect);
+        } else if let Some(renderer) = player.renderer_mut().downcast_mut::<OpenGlWgpuRenderBackend>() {
+            renderer.set_letterbox(config.letterbox_color, stage_rect);
+        }
     }
 
     fn handle_input(
         player: &mut Player,
         mouse_state: &mut MouseState,
+        held_keys: &mut Vec<retro_key>,
+        port_device: Option<u32>,
+        config: &Config,
         geometry: &retro_game_geometry,
         queued_events: &mut VecDeque<PlayerEvent>,
+        resumed: bool,
         ctx: &mut RunContext
-    ) {
+    ) -> bool {
         #[cfg(feature = "profiler")]
         profiling::scope!("Ruffle::handle_input");
+
+        // Mouse events are in the player's viewport, which is larger than the frame when Text Scale supersamples it
+        let viewport = player.renderer().viewport_dimensions();
+        let supersample = viewport.width as f32 / geometry.base_width.max(1) as f32;
+        let geometry = &retro_game_geometry {
+            base_width: viewport.width,
+            base_height: viewport.height,
+            ..*geometry
+        };
+
+        // Do as much as possible before polling, so the input is as fresh as it can be when dispatched
+        let interactive = player.mutate_with_update_context(|context| {
+            context.stage.display_state() != StageDisplayState::FullScreen
+        });
+
         {
             #[cfg(feature = "profiler")]
             profiling::scope!("retro_input_poll_t");
             ctx.poll_input();
         }
 
-        let new_mouse_state = mouse_state.from_context(geometry, ctx);
+        // If the frontend lost focus, it may never send the KeyUp for a held key
+        held_keys.retain(|&keycode| {
+            let pressed = ctx.get_input_state(0, RETRO_DEVICE_KEYBOARD, 0, keycode as u32) != 0;
+            if !pressed {
+                queued_events.push_back(PlayerEvent::KeyUp {
+                    key_code: util::keyboard::to_key_code(keycode),
+                    key_char: None,
+                });
+            }
+            pressed
+        });
 
-        if mouse_state.delta != Vector2D::zero() {
-            queued_events.push_back(PlayerEvent::MouseMove {
-                x: new_mouse_state.position.x as f64,
-                y: new_mouse_state.position.y as f64,
-            });
+        let new_mouse_state = if input::is_pointer(port_device) {
+            mouse_state.from_pointer(geometry, ctx)
+        } else if input::accepts_mouse(port_device) {
+            let new_mouse_state = mouse_state.from_context(
+                geometry,
+                ctx,
+                config.mouse_confine,
+                config.mouse_sensitivity * supersample,
+                config.analog_cursor,
+                config.mouse_axes,
+            );
+            if resumed {
+                // The mouse may have been moved around the frontend's menu, which isn't meant for the movie
+                new_mouse_state.without_motion(mouse_state)
+            } else {
+                new_mouse_state
+            }
+        } else {
+            // Keep the cursor where it was, so it's still there if a mouse is plugged back in
+            mouse_state.at_rest()
+        };
+
+        if new_mouse_state.position != mouse_state.position {
+            if new_mouse_state.is_on_stage(geometry) {
+                queued_events.push_back(PlayerEvent::MouseMove {
+                    x: new_mouse_state.position.x as f64,
+                    y: new_mouse_state.position.y as f64,
+                });
+            } else if mouse_state.is_on_stage(geometry) {
+                queued_events.push_back(PlayerEvent::MouseLeave);
+            }
         }
 
         match (mouse_state.button, new_mouse_state.button) {
@@ -550,16 +1734,65 @@ impl Ruffle {
             _ => {}
         };
 
+        if let Some(delta) = new_mouse_state.wheel_delta(config.wheel_invert) {
+            if new_mouse_state.is_on_stage(geometry) {
+                queued_events.push_back(PlayerEvent::MouseWheel { delta });
+            }
+        }
+
+        let had_input = !queued_events.is_empty();
+
+        // The keyboard callback and mouse polling don't share a clock, so put them in a fixed order instead
+        queued_events.make_contiguous().sort_by_key(input::dispatch_order);
+        let context_menu = config.right_click == RightClickMode::ContextMenu;
         for e in &mut *queued_events {
-            player.handle_event(*e);
+            match e {
+                PlayerEvent::MouseDown {
+                    button: MouseButton::Right,
+                    ..
+                } if context_menu => Self::show_context_menu(player, ctx),
+                PlayerEvent::MouseUp {
+                    button: MouseButton::Right,
+                    ..
+                } if context_menu => {}
+                e if interactive || input::allowed_in_fullscreen(e) => player.handle_event(*e),
+                _ => {}
+            }
         }
 
         queued_events.clear();
         *mouse_state = new_mouse_state;
+        had_input
+    }
+
+    /// The time between frames at the reported frame rate, in microseconds.
+    fn frame_time_us(av_info: &retro_system_av_info) -> Option<i64> {
+        let fps = av_info.timing.fps;
+        (fps.is_finite() && fps > 0.0).then(|| (1_000_000.0 / fps).round() as i64)
+    }
+
+    /// There's no way to show a real menu, so list what it would contain instead.
+    fn show_context_menu(player: &mut Player, ctx: &mut RunContext) {
+        let items = player.prepare_context_menu();
+        let captions: Vec<&str> = items
+            .iter()
+            .filter(|item| item.enabled)
+            .map(|item| item.caption.as_str())
+            .collect();
+        debug!("Context menu requested, items: {items:?}");
+
+        let message = format!("Context menu: {}", captions.join(", "));
+        let ctx = GenericContext::from(&mut *ctx);
+        Self::notify_info(&ctx, &message, CONTEXT_MENU_DURATION, CONTEXT_MENU_PRIORITY);
+        player.clear_custom_menu_items();
     }
 
-    fn render_graphics(player: &mut Player, av_info: &retro_system_av_info, ctx: &mut RunContext) {
-        if player.needs_render() {
+    /// Returns `true` if the player drew a new frame.
+    /// Renders the movie if it changed (or if `force` is set) and hands the frame to the frontend.
+    /// Returns whether anything was rendered.
+    fn render_graphics(player: &mut Player, av_info: &retro_system_av_info, force: bool, ctx: &mut RunContext) -> bool {
+        let needs_render = player.needs_render() || force;
+        if needs_render {
             #[cfg(feature = "profiler")]
             profiling::scope!("Player::render");
             player.render();
@@ -569,18 +1802,46 @@ impl Ruffle {
             #[cfg(feature = "profiler")]
             profiling::scope!("retro_video_refresh_t");
 
-            ctx.draw_hardware_frame(av_info.geometry.max_width, av_info.geometry.max_height, 0);
+            match player.renderer_mut().downcast_mut::<SoftwareWgpuRenderBackend>() {
+                Some(renderer) if needs_render => match renderer.capture_frame() {
+                    Some((frame, width, height)) => ctx.draw_frame(frame, width, height, (width * 4) as usize),
+                    None => ctx.dupe_frame(),
+                },
+                Some(_) => ctx.dupe_frame(),
+                None => ctx.draw_hardware_frame(av_info.geometry.max_width, av_info.geometry.max_height, 0),
+            }
         }
+
+        needs_render
     }
 
-    fn send_audio(player: &mut Player, ctx: &mut RunContext) {
+    /// Gives back part of the frame budget when there's nothing to do.
+    /// The frame time callback keeps ticking accurate, so this doesn't affect playback speed.
+    fn idle(av_info: &retro_system_av_info) {
+        let fps = av_info.timing.fps;
+        if fps.is_finite() && fps > 0.0 {
+            #[cfg(feature = "profiler")]
+            profiling::scope!("Ruffle::idle");
+            std::thread::sleep(Duration::from_secs_f64(POWER_SAVE_IDLE_FRACTION / fps));
+        }
+    }
+
+    /// Sends `delta_us` worth of audio, so the frontend gets as much audio as the movie played.
+    ///
+    /// While fast-forwarding or in slow motion, the frame time callback reports how much time the frontend
+    /// means each frame to cover, and the movie ticks by that much; mixing the same amount keeps the audio
+    /// in step with it instead of one nominal frame's worth per retro_run. If the frontend doesn't report
+    /// frame times, `on_run` falls back to the nominal frame time, which is also what this mixes.
+    fn send_audio(player: &mut Player, delta_us: i64, dump: Option<&mut WavWriter>, ctx: &mut RunContext) {
         #[cfg(feature = "profiler")]
         profiling::scope!("retro_run::handle_audio");
         let audio = player
-            .audio()
-            .downcast_ref::<RetroAudioBackend>()
+            .audio_mut()
+            .downcast_mut::<RetroAudioBackend>()
             .expect("Unexpected AudioBackend implementation");
 
+        // Mixed here rather than in Player::tick, which may run any number of movie frames per retro_run
+        audio.mix_frame(u64::try_from(delta_us).ok().map(Duration::from_micros));
         if let Some(samples) = audio.current_samples() {
             let ctx = AudioContext::from(ctx);
 
@@ -588,23 +1849,166 @@ impl Ruffle {
             profiling::scope!("retro_audio_sample_batch_t");
 
             ctx.batch_audio_samples(&samples[..]);
+
+            if let Some(dump) = dump {
+                if let Err(e) = dump.write_samples(samples) {
+                    warn!("Failed to dump audio: {e}");
+                }
+            }
+        }
+    }
+
+    /// Tells the renderer the audio's peak levels to draw meters for, or to hide the meters if they're not `visible`.
+    /// The meters show up with the next frame that's rendered; the fallback renderer doesn't draw them.
+    fn show_audio_levels(player: &mut Player, visible: bool) {
+        let levels = visible.then(|| {
+            player
+                .audio_mut()
+                .downcast_mut::<RetroAudioBackend>()
+                .expect("Unexpected AudioBackend implementation")
+                .peak_levels()
+        });
+
+        if let Some(renderer) = player.renderer_mut().downcast_mut::<VulkanWgpuRenderBackend>() {
+            renderer.set_audio_levels(levels);
+        } else if let Some(renderer) = player.renderer_mut().downcast_mut::<OpenGlWgpuRenderBackend>() {
+            renderer.set_audio_levels(levels);
+        }
+    }
+
+    /// Tells the renderer which cursor the movie wants and where the mouse is, or to hide the cursor if it's not
+    /// `visible` or the movie hid it. Returns whether that changed what's drawn; the fallback renderer doesn't draw it.
+    fn show_cursor(player: &mut Player, visible: bool, mouse_state: &MouseState) -> bool {
+        let cursor = player
+            .ui()
+            .downcast_ref::<RetroUiBackend>()
+            .expect("Unexpected UiBackend implementation")
+            .cursor()
+            .filter(|_| visible)
+            .map(|cursor| (cursor, [f32::from(mouse_state.position.x), f32::from(mouse_state.position.y)]));
+
+        if let Some(renderer) = player.renderer_mut().downcast_mut::<VulkanWgpuRenderBackend>() {
+            renderer.set_cursor(cursor)
+        } else if let Some(renderer) = player.renderer_mut().downcast_mut::<OpenGlWgpuRenderBackend>() {
+            renderer.set_cursor(cursor)
+        } else {
+            false
+        }
+    }
+
+    /// Winds down the active player before it's dropped, whether the frontend is closing the content or we are.
+    /// Saves the movie's SharedObjects like Flash Player does on exit, and silences it so audio doesn't cut off
+    /// mid-sound while the rest of the core shuts down.
+    fn shut_down_player(&mut self) {
+        if let Active(player) = &self.player {
+            #[cfg(feature = "profiler")]
+            profiling::scope!("Ruffle::shut_down_player");
+            let mut player = player.lock().unwrap();
+            player.flush_shared_objects();
+            player.set_is_playing(false);
+            player.audio_mut().stop_all_sounds();
+        }
+
+        // The writer finalizes the file when dropped
+        self.audio_dump = None;
+    }
+
+    /// Starts or stops dumping audio to match the config. Dropping the writer finalizes the file.
+    fn update_audio_dump(&mut self) {
+        if !self.config.audio_dump || matches!(self.player, Uninitialized | Exiting(_)) {
+            self.audio_dump = None;
+            return;
+        }
+
+        if self.audio_dump.is_some() {
+            return;
+        }
+
+        let directory = match unsafe { get_save_directory(self.environ_cb.get()) } {
+            Ok(Some(save_directory)) => save_directory.join(AUDIO_DUMP_DIRECTORY),
+            _ => {
+                warn!("No save directory available, can't dump audio");
+                return;
+            }
+        };
+
+        let sample_rate = self.active_sample_rate().unwrap_or(self.config.sample_rate);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let path = directory.join(format!("audio_dump_{timestamp}.wav"));
+        let result = std::fs::create_dir_all(&directory)
+            .and_then(|_| WavWriter::create(&path, sample_rate, AUDIO_DUMP_CHANNELS));
+        match result {
+            Ok(writer) => {
+                info!("Dumping audio to {path:?}");
+                self.audio_dump = Some(writer);
+            }
+            Err(e) => warn!("Failed to create audio dump {path:?}: {e}"),
         }
     }
 
     fn notify_context_lost(&self, ctx: &GenericContext) {
-        let message_sent = ctx.set_message_ext(
-            CONTEXT_LOST_MESSAGE,
-            CONTEXT_LOST_DURATION,
-            CONTEXT_LOST_PRIORITY,
-            retro_log_level::RETRO_LOG_ERROR,
-            retro_message_target::RETRO_MESSAGE_TARGET_ALL,
-            retro_message_type::RETRO_MESSAGE_TYPE_NOTIFICATION,
-            MessageProgress::Indeterminate,
-        );
+        Self::notify_error(ctx, CONTEXT_LOST_MESSAGE, CONTEXT_LOST_DURATION, CONTEXT_LOST_PRIORITY);
+    }
+
+    fn notify_info(ctx: &GenericContext, message: &str, duration: u32, priority: u32) {
+        let message_sent = unsafe {
+            let environ_cb = *ctx.environment_callback();
+            util::message::set_message(environ_cb, message, duration, priority, retro_log_level::RETRO_LOG_INFO)
+        };
+
+        if let Err(e) = message_sent {
+            info!("{message}");
+            warn!("Failed to show message: {e}");
+        }
+    }
+
+    /// Describes the active renderer, e.g. "Vulkan (wgpu), 1100x800, MSAA 4x".
+    fn render_status(player: &mut Player, msaa: u8) -> String {
+        let renderer = player.renderer_mut();
+        let dimensions = renderer.viewport_dimensions();
+        let msaa = match msaa {
+            0 | 1 => "MSAA off".to_string(),
+            samples => format!("MSAA {samples}x"),
+        };
+
+        format!("{}, {}x{}, {msaa}", renderer.name(), dimensions.width, dimensions.height)
+    }
+
+    fn notify_error(ctx: &GenericContext, message: &str, duration: u32, priority: u32) {
+        let message_sent = unsafe {
+            let environ_cb = *ctx.environment_callback();
+            util::message::set_message(environ_cb, message, duration, priority, retro_log_level::RETRO_LOG_ERROR)
+        };
 
         if let Err(e) = message_sent {
-            error!("{CONTEXT_LOST_MESSAGE}");
-            error!("Additionally, RETRO_ENVIRONMENT_SET_MESSAGE_EXT failed: {e}");
+            error!("{message}");
+            error!("Additionally, showing the message failed: {e}");
+        }
+    }
+
+    /// Reads the launcher movie that's shown when the core starts without content.
+    /// If there isn't one, tells the user how to provide it.
+    fn load_home_movie(&self, ctx: &GenericContext) -> Result<(Vec<u8>, PathBuf), CoreError> {
+        let path = match unsafe { get_system_directory(self.environ_cb.get()) } {
+            Ok(Some(system_directory)) => system_directory.join(HOME_MOVIE_PATH),
+            _ => {
+                Self::notify_error(ctx, NO_GAME_MESSAGE, NO_GAME_DURATION, NO_GAME_PRIORITY);
+                return Err(CoreError::NoGameProvided);
+            }
+        };
+
+        match std::fs::read(&path) {
+            Ok(buffer) => {
+                info!("No content provided, loading {path:?}");
+                Ok((buffer, path))
+            }
+            Err(e) => {
+                warn!("Couldn't read {path:?}: {e}");
+                Self::notify_error(ctx, NO_GAME_MESSAGE, NO_GAME_DURATION, NO_GAME_PRIORITY);
+                Err(CoreError::NoGameProvided)
+            }
         }
     }
 }
@@ -0,0 +1,41 @@
+use crate::options::EndBehavior;
+
+/// What to do to the root timeline once a finite movie is over.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EndAction {
+    Stop,
+    Restart,
+    Exit,
+}
+
+/// Watches the root timeline for the end of a movie.
+#[derive(Debug, Default)]
+pub struct EndWatcher {
+    previous_frame: u16,
+}
+
+impl EndWatcher {
+    /// `frame`, `total_frames`, and `playing` describe the root clip right after this frame's tick.
+    /// Single-frame movies are usually scripted content, not animations, so they never end.
+    pub fn update(&mut self, behavior: EndBehavior, frame: u16, total_frames: u16, playing: bool) -> Option<EndAction> {
+        let previous = std::mem::replace(&mut self.previous_frame, frame);
+        if total_frames <= 1 {
+            return None;
+        }
+
+        // The last frame has been shown, and the movie either stopped there or wrapped around
+        let finished = previous == total_frames && (frame < total_frames || !playing);
+        match behavior {
+            EndBehavior::Loop => None,
+            // Stopped as soon as the last frame is reached, so a looping movie never gets to wrap.
+            // Only on arrival, though; an end screen's button may play() on from there, and should be allowed to.
+            // Input keeps flowing either way, since only the root clip is stopped, not the player.
+            EndBehavior::Freeze => {
+                (frame == total_frames && previous != total_frames && playing).then_some(EndAction::Stop)
+            }
+            // A movie that wrapped around has already restarted on its own
+            EndBehavior::Restart => (finished && frame == total_frames).then_some(EndAction::Restart),
+            EndBehavior::Shutdown => finished.then_some(EndAction::Exit),
+        }
+    }
+}
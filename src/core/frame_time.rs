@@ -0,0 +1,43 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use rust_libretro::anyhow;
+use rust_libretro::sys::{
+    retro_environment_t, retro_frame_time_callback, retro_usec_t, RETRO_ENVIRONMENT_SET_FRAME_TIME_CALLBACK,
+};
+
+/// Stands in for "no frame time reported since the last frame"; no frontend reports a time this negative.
+const NO_FRAME_TIME: i64 = i64::MIN;
+
+static FRAME_TIME: AtomicI64 = AtomicI64::new(NO_FRAME_TIME);
+
+/// Registers the frame time callback with a reference time that matches `frame_rate`.
+/// The core does this itself rather than through `LoadGameContext::enable_frame_time_callback`,
+/// since that's only available while loading and movies can change their frame rate at any time.
+pub unsafe fn register(environ_cb: retro_environment_t, frame_rate: f64) -> anyhow::Result<()> {
+    let environ_cb = environ_cb.ok_or_else(|| anyhow::anyhow!("No environment callback"))?;
+    let mut callback = retro_frame_time_callback {
+        callback: Some(on_frame_time),
+        reference: (1_000_000.0 / frame_rate).round() as retro_usec_t,
+    };
+
+    if environ_cb(
+        RETRO_ENVIRONMENT_SET_FRAME_TIME_CALLBACK,
+        (&mut callback as *mut retro_frame_time_callback).cast(),
+    ) {
+        Ok(())
+    } else {
+        anyhow::bail!("The frontend rejected the frame time callback")
+    }
+}
+
+/// The time the frontend says passed before this frame, if it said so since the last call.
+pub fn take() -> Option<retro_usec_t> {
+    match FRAME_TIME.swap(NO_FRAME_TIME, Ordering::Relaxed) {
+        NO_FRAME_TIME => None,
+        usec => Some(usec),
+    }
+}
+
+unsafe extern "C" fn on_frame_time(usec: retro_usec_t) {
+    FRAME_TIME.store(usec, Ordering::Relaxed);
+}
@@ -1,9 +1,13 @@
 use std::ptr;
 
+use ruffle_core::events::KeyCode;
+use ruffle_core::PlayerEvent;
 use rust_libretro::{input_descriptors, c_char_ptr};
 use rust_libretro_sys::retro_input_descriptor;
 use rust_libretro_sys::*;
 
+use crate::options::KeyboardPassthrough;
+
 pub const INPUT_DESCRIPTORS: &[retro_input_descriptor] = &input_descriptors!(
     { 0, RETRO_DEVICE_KEYBOARD, 0, RETRO_DEVICE_ID_JOYPAD_UP, "Up Key" },
     { 0, RETRO_DEVICE_KEYBOARD, 0, RETRO_DEVICE_ID_JOYPAD_DOWN, "Down Key" },
@@ -11,8 +15,89 @@ pub const INPUT_DESCRIPTORS: &[retro_input_descriptor] = &input_descriptors!(
     { 0, RETRO_DEVICE_KEYBOARD, 0, RETRO_DEVICE_ID_JOYPAD_RIGHT, "Right Key" },
     { 0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_LEFT, "Left Mouse Button" },
     { 0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_RIGHT, "Right Mouse Button" },
+    { 0, RETRO_DEVICE_ANALOG, RETRO_DEVICE_INDEX_ANALOG_LEFT, RETRO_DEVICE_ID_ANALOG_X, "Cursor X" },
+    { 0, RETRO_DEVICE_ANALOG, RETRO_DEVICE_INDEX_ANALOG_LEFT, RETRO_DEVICE_ID_ANALOG_Y, "Cursor Y" },
+);
+
+pub const KEYBOARD_INPUT_DESCRIPTORS: &[retro_input_descriptor] = &input_descriptors!(
+    { 0, RETRO_DEVICE_KEYBOARD, 0, RETRO_DEVICE_ID_JOYPAD_UP, "Up Key" },
+    { 0, RETRO_DEVICE_KEYBOARD, 0, RETRO_DEVICE_ID_JOYPAD_DOWN, "Down Key" },
+    { 0, RETRO_DEVICE_KEYBOARD, 0, RETRO_DEVICE_ID_JOYPAD_LEFT, "Left Key" },
+    { 0, RETRO_DEVICE_KEYBOARD, 0, RETRO_DEVICE_ID_JOYPAD_RIGHT, "Right Key" },
+);
+
+pub const MOUSE_INPUT_DESCRIPTORS: &[retro_input_descriptor] = &input_descriptors!(
+    { 0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_LEFT, "Left Mouse Button" },
+    { 0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_RIGHT, "Right Mouse Button" },
+    { 0, RETRO_DEVICE_ANALOG, RETRO_DEVICE_INDEX_ANALOG_LEFT, RETRO_DEVICE_ID_ANALOG_X, "Cursor X" },
+    { 0, RETRO_DEVICE_ANALOG, RETRO_DEVICE_INDEX_ANALOG_LEFT, RETRO_DEVICE_ID_ANALOG_Y, "Cursor Y" },
 );
 
+/// Returns the input descriptors that apply to the given device,
+/// or all of them if no device has been selected yet.
+pub fn input_descriptors(device: Option<u32>) -> &'static [retro_input_descriptor] {
+    match device.map(|d| d & RETRO_DEVICE_MASK) {
+        Some(RETRO_DEVICE_KEYBOARD) => KEYBOARD_INPUT_DESCRIPTORS,
+        Some(RETRO_DEVICE_MOUSE) | Some(RETRO_DEVICE_POINTER) => MOUSE_INPUT_DESCRIPTORS,
+        _ => INPUT_DESCRIPTORS,
+    }
+}
+
+/// Whether keyboard events should reach the player when the given device is selected.
+/// Devices this core doesn't offer (e.g. the RetroPad frontends default to) accept everything.
+pub fn accepts_keyboard(device: Option<u32>) -> bool {
+    !is_offered(device) || device.map(|d| d & RETRO_DEVICE_MASK) == Some(RETRO_DEVICE_KEYBOARD)
+}
+
+/// Whether the mouse should be polled when the given device is selected.
+/// Pointer devices drive the mouse too; see [`is_pointer`].
+pub fn accepts_mouse(device: Option<u32>) -> bool {
+    !is_offered(device)
+        || matches!(
+            device.map(|d| d & RETRO_DEVICE_MASK),
+            Some(RETRO_DEVICE_MOUSE | RETRO_DEVICE_POINTER)
+        )
+}
+
+/// Whether the given device is one this core offers, rather than none at all or something generic like a joypad.
+pub fn is_offered(device: Option<u32>) -> bool {
+    matches!(
+        device.map(|d| d & RETRO_DEVICE_MASK),
+        Some(RETRO_DEVICE_KEYBOARD) | Some(RETRO_DEVICE_MOUSE) | Some(RETRO_DEVICE_POINTER)
+    )
+}
+
+/// Whether the given device is a touchscreen or other absolute pointer, rather than a relative mouse.
+pub fn is_pointer(device: Option<u32>) -> bool {
+    device.map(|d| d & RETRO_DEVICE_MASK) == Some(RETRO_DEVICE_POINTER)
+}
+
+/// Whether `key` is kept from the content so that it only triggers the frontend's hotkey.
+/// The frontend sees every key either way; this just keeps the movie from reacting to it too.
+pub fn reserved_for_frontend(key: retro_key, passthrough: KeyboardPassthrough) -> bool {
+    use retro_key::*;
+    match passthrough {
+        KeyboardPassthrough::All => false,
+        KeyboardPassthrough::ReserveMenu => matches!(key, RETROK_ESCAPE | RETROK_F1),
+        KeyboardPassthrough::ReserveFunctionKeys => matches!(
+            key,
+            RETROK_ESCAPE
+                | RETROK_F1
+                | RETROK_F2
+                | RETROK_F3
+                | RETROK_F4
+                | RETROK_F5
+                | RETROK_F6
+                | RETROK_F7
+                | RETROK_F8
+                | RETROK_F9
+                | RETROK_F10
+                | RETROK_F11
+                | RETROK_F12
+        ),
+    }
+}
+
 // TODO: Add a Keyboard subclass with just the supported keys
 pub const CONTROLLER_DESCRIPTIONS: &[retro_controller_description] = &[
     retro_controller_description {
@@ -34,4 +119,39 @@ pub const CONTROLLER_INFO: &[retro_controller_info] = &[
         num_types: CONTROLLER_DESCRIPTIONS.len() as u32,
     },
     retro_controller_info { types: ptr::null(), num_types: 0 },
-];
\ No newline at end of file
+];
+
+/// Returns `true` if Flash Player would deliver `event` while the stage is in
+/// non-interactive fullscreen, which only allows non-printing keys.
+/// Key releases are always let through so that no key stays held.
+pub fn allowed_in_fullscreen(event: &PlayerEvent) -> bool {
+    match event {
+        PlayerEvent::KeyDown { key_code, .. } => matches!(
+            key_code,
+            KeyCode::Left
+                | KeyCode::Up
+                | KeyCode::Right
+                | KeyCode::Down
+                | KeyCode::Space
+                | KeyCode::Tab
+                | KeyCode::Escape
+                | KeyCode::PgUp
+                | KeyCode::PgDown
+                | KeyCode::Home
+                | KeyCode::End
+        ),
+        PlayerEvent::TextInput { .. } | PlayerEvent::TextControl { .. } => false,
+        _ => true,
+    }
+}
+
+/// Where `event` belongs among a frame's input: key presses first, then the mouse, then key releases.
+/// This way a modifier that's pressed and released within the same frame as a click still applies to it.
+/// Events are stably sorted by this, so each group keeps its own order.
+pub fn dispatch_order(event: &PlayerEvent) -> u8 {
+    match event {
+        PlayerEvent::KeyDown { .. } | PlayerEvent::TextInput { .. } | PlayerEvent::TextControl { .. } => 0,
+        PlayerEvent::KeyUp { .. } => 2,
+        _ => 1,
+    }
+}
@@ -0,0 +1,141 @@
+use std::cell::RefCell;
+use std::ffi::{c_void, CString};
+use std::os::raw::c_char;
+use std::ptr;
+
+use rust_libretro::anyhow;
+use rust_libretro::sys::{
+    retro_core_option_definition, retro_core_option_value, retro_environment_t, retro_variable,
+    RETRO_ENVIRONMENT_SET_CORE_OPTIONS, RETRO_ENVIRONMENT_SET_VARIABLES, RETRO_NUM_CORE_OPTION_VALUES_MAX,
+};
+
+/// One of the core's options as declared in `#[options(...)]`, without its category;
+/// build.rs copies them here, since the derived `set_core_options` only sends the v2 layout.
+pub struct LegacyOption {
+    pub key: &'static str,
+    /// Prefixed with the option's category (e.g. "Video > Letterbox"), since there's nothing else to group it by
+    pub desc: &'static str,
+    pub info: &'static str,
+    /// Each value, and the label to show for it if it has one
+    pub values: &'static [(&'static str, Option<&'static str>)],
+    /// The first value if `None`
+    pub default: Option<&'static str>,
+}
+
+include!(concat!(env!("OUT_DIR"), "/legacy_options.rs"));
+
+/// Sends the options with RETRO_ENVIRONMENT_SET_CORE_OPTIONS, for frontends that support v1 options.
+pub unsafe fn set_core_options_v1(environ_cb: retro_environment_t) -> anyhow::Result<()> {
+    let strings = Strings::new();
+    let mut definitions: Vec<retro_core_option_definition> = LEGACY_OPTIONS
+        .iter()
+        .map(|option| {
+            let mut values = [retro_core_option_value {
+                value: ptr::null(),
+                label: ptr::null(),
+            }; RETRO_NUM_CORE_OPTION_VALUES_MAX as usize];
+            // The array has to end with a null value
+            let slots = values.iter_mut().take(RETRO_NUM_CORE_OPTION_VALUES_MAX as usize - 1);
+            for (slot, (value, label)) in slots.zip(option.values) {
+                slot.value = strings.get(value);
+                slot.label = label.map_or(ptr::null(), |label| strings.get(label));
+            }
+
+            retro_core_option_definition {
+                key: strings.get(option.key),
+                desc: strings.get(option.desc),
+                info: strings.get(option.info),
+                values,
+                default_value: option.default.map_or(ptr::null(), |default| strings.get(default)),
+            }
+        })
+        .collect();
+    definitions.push(retro_core_option_definition {
+        key: ptr::null(),
+        desc: ptr::null(),
+        info: ptr::null(),
+        values: [retro_core_option_value {
+            value: ptr::null(),
+            label: ptr::null(),
+        }; RETRO_NUM_CORE_OPTION_VALUES_MAX as usize],
+        default_value: ptr::null(),
+    });
+
+    // The frontend copies everything it needs before returning
+    environment(environ_cb, RETRO_ENVIRONMENT_SET_CORE_OPTIONS, definitions.as_mut_ptr().cast())
+}
+
+/// Sends the options with RETRO_ENVIRONMENT_SET_VARIABLES, for frontends that don't support core options at all.
+/// There are no labels or explanations, and the default has to come first.
+pub unsafe fn set_variables(environ_cb: retro_environment_t) -> anyhow::Result<()> {
+    let strings = Strings::new();
+    let mut variables: Vec<retro_variable> = LEGACY_OPTIONS
+        .iter()
+        .map(|option| {
+            let default = option.default.or_else(|| option.values.first().map(|(value, _)| *value));
+            let others = option.values.iter().map(|(value, _)| *value).filter(|value| Some(*value) != default);
+            let values: Vec<&str> = default.into_iter().chain(others).collect();
+            retro_variable {
+                key: strings.get(option.key),
+                value: strings.get(&format!("{}; {}", option.desc, values.join("|"))),
+            }
+        })
+        .collect();
+    variables.push(retro_variable {
+        key: ptr::null(),
+        value: ptr::null(),
+    });
+
+    environment(environ_cb, RETRO_ENVIRONMENT_SET_VARIABLES, variables.as_mut_ptr().cast())
+}
+
+unsafe fn environment(environ_cb: retro_environment_t, cmd: u32, data: *mut c_void) -> anyhow::Result<()> {
+    let environ_cb = environ_cb.ok_or_else(|| anyhow::anyhow!("No environment callback"))?;
+    if environ_cb(cmd, data) {
+        Ok(())
+    } else {
+        anyhow::bail!("The frontend rejected environment command {cmd}")
+    }
+}
+
+/// Keeps the C strings handed to the frontend alive until it's done with them.
+struct Strings(RefCell<Vec<CString>>);
+
+impl Strings {
+    fn new() -> Self {
+        Self(Default::default())
+    }
+
+    fn get(&self, string: &str) -> *const c_char {
+        // None of the options have a NUL in them, but an empty string is better than a panic if one does
+        let string = CString::new(string).unwrap_or_default();
+        // Moving a CString doesn't move its heap buffer, so the pointer stays valid
+        let pointer = string.as_ptr();
+        self.0.borrow_mut().push(string);
+        pointer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_option_has_a_valid_default() {
+        for option in LEGACY_OPTIONS {
+            assert!(!option.values.is_empty(), "{} has no values", option.key);
+            assert!(
+                option.values.len() < RETRO_NUM_CORE_OPTION_VALUES_MAX as usize,
+                "{} has too many values",
+                option.key
+            );
+            if let Some(default) = option.default {
+                assert!(
+                    option.values.iter().any(|(value, _)| *value == default),
+                    "{}'s default {default} isn't one of its values",
+                    option.key
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+
+/// Caps how often the player ticks and how far its timeline advances, measured against the real clock.
+/// Protects against frontends that run the core faster than real time,
+/// e.g. at a high refresh rate without the frame time callback.
+#[derive(Debug)]
+pub struct FrameLimiter {
+    max_fps: u32,
+    min_interval: Duration,
+    last_tick: Option<Instant>,
+    /// Time the frontend says has passed since the last tick
+    pending_us: i64,
+}
+
+impl FrameLimiter {
+    pub fn new(max_fps: u32) -> Self {
+        Self {
+            max_fps,
+            min_interval: Duration::from_secs(1) / max_fps.max(1),
+            last_tick: None,
+            pending_us: 0,
+        }
+    }
+
+    pub fn max_fps(&self) -> u32 {
+        self.max_fps
+    }
+
+    /// Returns how far to advance the player, or `None` if it's too soon since the last tick.
+    /// Skipped time is carried over, but never beyond what really elapsed.
+    pub fn admit(&mut self, delta_us: i64, now: Instant) -> Option<i64> {
+        self.pending_us = self.pending_us.saturating_add(delta_us);
+
+        let real_us = match self.last_tick {
+            Some(last_tick) if now.duration_since(last_tick) < self.min_interval => return None,
+            Some(last_tick) => now.duration_since(last_tick).as_micros().min(i64::MAX as u128) as i64,
+            None => self.pending_us,
+        };
+
+        self.last_tick = Some(now);
+        Some(std::mem::take(&mut self.pending_us).min(real_us))
+    }
+}
@@ -0,0 +1,39 @@
+/// How much of the accumulated timing error to pay back each frame.
+/// Small enough to hide jitter, large enough that playback never drifts far from real time.
+const CORRECTION_RATE: f64 = 0.1;
+
+/// If the paced clock falls this many frames behind or ahead (e.g. after a hitch or a pause),
+/// give up on smoothing it over and jump straight to real time.
+const MAX_ERROR_FRAMES: f64 = 4.0;
+
+/// Smooths the frontend's frame times toward the nominal frame time,
+/// while keeping the total elapsed time in step with the frontend's.
+#[derive(Debug)]
+pub struct FramePacer {
+    nominal_us: f64,
+    /// Real elapsed time minus paced elapsed time
+    error_us: f64,
+}
+
+impl FramePacer {
+    pub fn new(nominal_us: i64) -> Self {
+        Self {
+            nominal_us: nominal_us as f64,
+            error_us: 0.0,
+        }
+    }
+
+    /// Returns the delta to advance the player by, given the frontend's reported delta.
+    pub fn pace(&mut self, delta_us: i64) -> i64 {
+        self.error_us += delta_us as f64;
+
+        if self.error_us.abs() > self.nominal_us * MAX_ERROR_FRAMES {
+            self.error_us = 0.0;
+            return delta_us;
+        }
+
+        let paced = (self.nominal_us + (self.error_us - self.nominal_us) * CORRECTION_RATE).max(0.0);
+        self.error_us -= paced;
+        paced.round() as i64
+    }
+}
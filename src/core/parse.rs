@@ -0,0 +1,310 @@
+//! Parsers for core option values, kept separate from `on_options_changed` so each one is easy to check by hand.
+//! Every parser takes the option's value (`None` if the frontend didn't provide one)
+//! and falls back to the option's default for anything it doesn't recognize.
+
+use std::time::Duration;
+
+use super::config::defaults;
+use crate::options::LetterboxColor;
+
+/// The value of the "No Limit" choice for `ruffle_max_execution_duration`.
+/// It's 2^64, which doesn't fit in a `u64`; it's kept as-is so existing settings files still work.
+const NO_EXECUTION_LIMIT: &str = "18446744073709551616";
+
+pub fn flag(value: Option<&str>, default: bool) -> bool {
+    match value {
+        Some("true") => true,
+        Some("false") => false,
+        _ => default,
+    }
+}
+
+pub fn max_execution_duration(value: Option<&str>) -> Duration {
+    match value {
+        Some(NO_EXECUTION_LIMIT) => Duration::MAX,
+        Some(seconds) => seconds
+            .parse::<u64>()
+            .map(Duration::from_secs)
+            .unwrap_or(defaults::MAX_EXECUTION_DURATION),
+        None => defaults::MAX_EXECUTION_DURATION,
+    }
+}
+
+/// Accepts "black", "stage", or a custom color as `#rrggbb`.
+pub fn letterbox_color(value: Option<&str>) -> LetterboxColor {
+    match value {
+        Some("black") => LetterboxColor::Black,
+        Some("stage") => LetterboxColor::Stage,
+        Some(hex) => hex
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6)
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .map(|rgb| LetterboxColor::Custom([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8]))
+            .unwrap_or(defaults::LETTERBOX_COLOR),
+        None => defaults::LETTERBOX_COLOR,
+    }
+}
+
+pub fn msaa(value: Option<&str>) -> u8 {
+    value.and_then(|s| s.parse::<u8>().ok()).unwrap_or(defaults::MSAA)
+}
+
+pub fn text_scale(value: Option<&str>) -> f64 {
+    value
+        .and_then(|s| s.parse::<f64>().ok())
+        .filter(|scale| scale.is_finite() && *scale >= 1.0)
+        .unwrap_or(defaults::TEXT_SCALE)
+}
+
+pub fn render_scale(value: Option<&str>) -> f64 {
+    value
+        .and_then(|s| s.parse::<f64>().ok())
+        .filter(|scale| scale.is_finite() && *scale > 0.0 && *scale <= 1.0)
+        .unwrap_or(defaults::RENDER_SCALE)
+}
+
+pub fn benchmark_frames(value: Option<&str>) -> Option<u32> {
+    match value {
+        Some("off") => None,
+        Some(frames) => frames.parse::<u32>().ok().filter(|f| *f > 0).or(defaults::BENCHMARK_FRAMES),
+        None => defaults::BENCHMARK_FRAMES,
+    }
+}
+
+/// Megabytes in the option, bytes in the result.
+pub fn max_bitmap_memory(value: Option<&str>) -> Option<usize> {
+    match value {
+        Some("off") => None,
+        Some(megabytes) => megabytes
+            .parse::<usize>()
+            .ok()
+            .and_then(|megabytes| megabytes.checked_mul(1024 * 1024))
+            .or(defaults::MAX_BITMAP_MEMORY),
+        None => defaults::MAX_BITMAP_MEMORY,
+    }
+}
+
+pub fn vulkan_frames(value: Option<&str>) -> Option<u32> {
+    match value {
+        Some("auto") => None,
+        Some(frames) => frames.parse::<u32>().ok().or(defaults::VULKAN_FRAMES),
+        None => defaults::VULKAN_FRAMES,
+    }
+}
+
+pub fn max_fps(value: Option<&str>) -> Option<u32> {
+    match value {
+        Some("off") => None,
+        Some(fps) => fps.parse::<u32>().ok().filter(|fps| *fps > 0).or(defaults::MAX_FPS),
+        None => defaults::MAX_FPS,
+    }
+}
+
+/// The frame to show while waiting to be played; "last" is clamped to the movie's length later.
+pub fn paused_frame(value: Option<&str>) -> u16 {
+    match value {
+        Some("last") => u16::MAX,
+        Some(frame) => frame.parse::<u16>().ok().filter(|frame| *frame > 0).unwrap_or(defaults::PAUSED_FRAME),
+        None => defaults::PAUSED_FRAME,
+    }
+}
+
+pub fn max_requests(value: Option<&str>) -> Option<usize> {
+    match value {
+        Some("unlimited") => None,
+        Some(count) => count.parse::<usize>().ok().filter(|count| *count > 0).or(defaults::MAX_REQUESTS),
+        None => defaults::MAX_REQUESTS,
+    }
+}
+
+pub fn key_repeat_delay(value: Option<&str>) -> Option<Duration> {
+    match value {
+        Some("off") => None,
+        Some(ms) => ms.parse::<u64>().ok().map(Duration::from_millis).or(defaults::KEY_REPEAT_DELAY),
+        None => defaults::KEY_REPEAT_DELAY,
+    }
+}
+
+/// Converts repeats per second to the time between them.
+pub fn key_repeat_interval(value: Option<&str>) -> Duration {
+    value
+        .and_then(|s| s.parse::<u32>().ok())
+        .filter(|rate| *rate > 0)
+        .map_or(defaults::KEY_REPEAT_INTERVAL, |rate| Duration::from_secs(1) / rate)
+}
+
+pub fn sample_rate(value: Option<&str>) -> u32 {
+    value
+        .and_then(|s| s.parse::<u32>().ok())
+        .filter(|rate| *rate > 0)
+        .unwrap_or(defaults::SAMPLE_RATE)
+}
+
+/// Converts a percentage to a volume from 0.0 to 1.0, clamping anything louder than 100%.
+pub fn volume(value: Option<&str>) -> f32 {
+    value
+        .and_then(|s| s.parse::<u32>().ok())
+        .map_or(defaults::VOLUME, |percent| percent.min(100) as f32 / 100.0)
+}
+
+pub fn mouse_sensitivity(value: Option<&str>) -> f32 {
+    value
+        .and_then(|s| s.parse::<f32>().ok())
+        .filter(|sensitivity| sensitivity.is_finite() && *sensitivity > 0.0)
+        .unwrap_or(defaults::MOUSE_SENSITIVITY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flag_values() {
+        assert!(flag(Some("true"), false));
+        assert!(!flag(Some("false"), true));
+        assert!(flag(Some("yes"), true));
+        assert!(!flag(None, false));
+    }
+
+    #[test]
+    fn max_execution_duration_values() {
+        assert_eq!(max_execution_duration(Some("30")), Duration::from_secs(30));
+        assert_eq!(max_execution_duration(Some("18446744073709551616")), Duration::MAX);
+        assert_eq!(max_execution_duration(Some("-1")), defaults::MAX_EXECUTION_DURATION);
+        assert_eq!(max_execution_duration(Some("forever")), defaults::MAX_EXECUTION_DURATION);
+        assert_eq!(max_execution_duration(None), defaults::MAX_EXECUTION_DURATION);
+    }
+
+    #[test]
+    fn letterbox_color_values() {
+        assert_eq!(letterbox_color(Some("black")), LetterboxColor::Black);
+        assert_eq!(letterbox_color(Some("stage")), LetterboxColor::Stage);
+        assert_eq!(letterbox_color(Some("#12abEF")), LetterboxColor::Custom([0x12, 0xab, 0xef]));
+        assert_eq!(letterbox_color(Some("12abef")), defaults::LETTERBOX_COLOR);
+        assert_eq!(letterbox_color(Some("#12abe")), defaults::LETTERBOX_COLOR);
+        assert_eq!(letterbox_color(Some("#12abeg")), defaults::LETTERBOX_COLOR);
+        assert_eq!(letterbox_color(None), defaults::LETTERBOX_COLOR);
+    }
+
+    #[test]
+    fn msaa_values() {
+        assert_eq!(msaa(Some("4")), 4);
+        assert_eq!(msaa(Some("256")), defaults::MSAA);
+        assert_eq!(msaa(Some("many")), defaults::MSAA);
+        assert_eq!(msaa(None), defaults::MSAA);
+    }
+
+    #[test]
+    fn text_scale_values() {
+        assert_eq!(text_scale(Some("2")), 2.0);
+        assert_eq!(text_scale(Some("0.5")), defaults::TEXT_SCALE);
+        assert_eq!(text_scale(Some("inf")), defaults::TEXT_SCALE);
+        assert_eq!(text_scale(Some("big")), defaults::TEXT_SCALE);
+        assert_eq!(text_scale(None), defaults::TEXT_SCALE);
+    }
+
+    #[test]
+    fn render_scale_values() {
+        assert_eq!(render_scale(Some("0.5")), 0.5);
+        assert_eq!(render_scale(Some("1")), 1.0);
+        assert_eq!(render_scale(Some("0")), defaults::RENDER_SCALE);
+        assert_eq!(render_scale(Some("2")), defaults::RENDER_SCALE);
+        assert_eq!(render_scale(Some("NaN")), defaults::RENDER_SCALE);
+        assert_eq!(render_scale(None), defaults::RENDER_SCALE);
+    }
+
+    #[test]
+    fn benchmark_frames_values() {
+        assert_eq!(benchmark_frames(Some("off")), None);
+        assert_eq!(benchmark_frames(Some("600")), Some(600));
+        assert_eq!(benchmark_frames(Some("0")), defaults::BENCHMARK_FRAMES);
+        assert_eq!(benchmark_frames(Some("lots")), defaults::BENCHMARK_FRAMES);
+        assert_eq!(benchmark_frames(None), defaults::BENCHMARK_FRAMES);
+    }
+
+    #[test]
+    fn max_bitmap_memory_values() {
+        assert_eq!(max_bitmap_memory(Some("off")), None);
+        assert_eq!(max_bitmap_memory(Some("256")), Some(256 * 1024 * 1024));
+        assert_eq!(max_bitmap_memory(Some(&usize::MAX.to_string())), defaults::MAX_BITMAP_MEMORY);
+        assert_eq!(max_bitmap_memory(Some("lots")), defaults::MAX_BITMAP_MEMORY);
+        assert_eq!(max_bitmap_memory(None), defaults::MAX_BITMAP_MEMORY);
+    }
+
+    #[test]
+    fn vulkan_frames_values() {
+        assert_eq!(vulkan_frames(Some("auto")), None);
+        assert_eq!(vulkan_frames(Some("3")), Some(3));
+        assert_eq!(vulkan_frames(Some("three")), defaults::VULKAN_FRAMES);
+        assert_eq!(vulkan_frames(None), defaults::VULKAN_FRAMES);
+    }
+
+    #[test]
+    fn max_fps_values() {
+        assert_eq!(max_fps(Some("off")), None);
+        assert_eq!(max_fps(Some("30")), Some(30));
+        assert_eq!(max_fps(Some("0")), defaults::MAX_FPS);
+        assert_eq!(max_fps(Some("fast")), defaults::MAX_FPS);
+        assert_eq!(max_fps(None), defaults::MAX_FPS);
+    }
+
+    #[test]
+    fn paused_frame_values() {
+        assert_eq!(paused_frame(Some("last")), u16::MAX);
+        assert_eq!(paused_frame(Some("5")), 5);
+        assert_eq!(paused_frame(Some("0")), defaults::PAUSED_FRAME);
+        assert_eq!(paused_frame(Some("65536")), defaults::PAUSED_FRAME);
+        assert_eq!(paused_frame(None), defaults::PAUSED_FRAME);
+    }
+
+    #[test]
+    fn max_requests_values() {
+        assert_eq!(max_requests(Some("unlimited")), None);
+        assert_eq!(max_requests(Some("4")), Some(4));
+        assert_eq!(max_requests(Some("0")), defaults::MAX_REQUESTS);
+        assert_eq!(max_requests(Some("some")), defaults::MAX_REQUESTS);
+        assert_eq!(max_requests(None), defaults::MAX_REQUESTS);
+    }
+
+    #[test]
+    fn key_repeat_delay_values() {
+        assert_eq!(key_repeat_delay(Some("off")), None);
+        assert_eq!(key_repeat_delay(Some("500")), Some(Duration::from_millis(500)));
+        assert_eq!(key_repeat_delay(Some("soon")), defaults::KEY_REPEAT_DELAY);
+        assert_eq!(key_repeat_delay(None), defaults::KEY_REPEAT_DELAY);
+    }
+
+    #[test]
+    fn key_repeat_interval_values() {
+        assert_eq!(key_repeat_interval(Some("20")), Duration::from_millis(50));
+        assert_eq!(key_repeat_interval(Some("0")), defaults::KEY_REPEAT_INTERVAL);
+        assert_eq!(key_repeat_interval(Some("often")), defaults::KEY_REPEAT_INTERVAL);
+        assert_eq!(key_repeat_interval(None), defaults::KEY_REPEAT_INTERVAL);
+    }
+
+    #[test]
+    fn sample_rate_values() {
+        assert_eq!(sample_rate(Some("48000")), 48000);
+        assert_eq!(sample_rate(Some("0")), defaults::SAMPLE_RATE);
+        assert_eq!(sample_rate(Some("cd")), defaults::SAMPLE_RATE);
+        assert_eq!(sample_rate(None), defaults::SAMPLE_RATE);
+    }
+
+    #[test]
+    fn volume_values() {
+        assert_eq!(volume(Some("50")), 0.5);
+        assert_eq!(volume(Some("0")), 0.0);
+        assert_eq!(volume(Some("150")), 1.0);
+        assert_eq!(volume(Some("-10")), defaults::VOLUME);
+        assert_eq!(volume(None), defaults::VOLUME);
+    }
+
+    #[test]
+    fn mouse_sensitivity_values() {
+        assert_eq!(mouse_sensitivity(Some("1.5")), 1.5);
+        assert_eq!(mouse_sensitivity(Some("0")), defaults::MOUSE_SENSITIVITY);
+        assert_eq!(mouse_sensitivity(Some("inf")), defaults::MOUSE_SENSITIVITY);
+        assert_eq!(mouse_sensitivity(Some("fast")), defaults::MOUSE_SENSITIVITY);
+        assert_eq!(mouse_sensitivity(None), defaults::MOUSE_SENSITIVITY);
+    }
+}
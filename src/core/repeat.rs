@@ -0,0 +1,48 @@
+use std::time::{Duration, Instant};
+
+use rust_libretro_sys::retro_key;
+
+/// The most repeats to send in one frame, so a stalled frame doesn't unleash a burst of them.
+const MAX_REPEATS_PER_POLL: u32 = 4;
+
+/// Repeats a held key the way an OS would, for frontends that only report the initial press.
+#[derive(Debug, Default)]
+pub struct KeyRepeat {
+    /// The key that repeats, and when it's next due to
+    repeating: Option<(retro_key, Instant)>,
+}
+
+impl KeyRepeat {
+    /// Only the most recently pressed key repeats.
+    /// Repeats sent by the frontend itself come through here too and push ours back, so the two don't pile up.
+    pub fn press(&mut self, key: retro_key, now: Instant, delay: Duration) {
+        self.repeating = Some((key, now + delay));
+    }
+
+    pub fn release(&mut self, key: retro_key) {
+        if matches!(self.repeating, Some((repeating, _)) if repeating == key) {
+            self.repeating = None;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.repeating = None;
+    }
+
+    /// Returns the repeating key and how many times it's repeated since the last poll, if at all.
+    pub fn poll(&mut self, now: Instant, interval: Duration) -> Option<(retro_key, u32)> {
+        let (key, next) = self.repeating.as_mut()?;
+        let mut count = 0;
+        while *next <= now && count < MAX_REPEATS_PER_POLL {
+            count += 1;
+            *next += interval;
+        }
+
+        if *next <= now {
+            // Too far behind to catch up
+            *next = now + interval;
+        }
+
+        (count > 0).then_some((*key, count))
+    }
+}
@@ -7,5 +7,14 @@ pub enum PlayerState {
     Uninitialized,
     Pending(Cell<PlayerBuilder>),
     Active(Arc<Mutex<Player>>),
-    Exiting,
+    Exiting(ExitReason),
+}
+
+/// Why the core is shutting down, so the frontend and user can tell a clean exit from a failure.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExitReason {
+    /// The user or the content asked to close the core.
+    Requested,
+    /// The hardware context was lost and can't be recreated.
+    ContextLost,
 }
\ No newline at end of file
@@ -1,11 +1,199 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum FileAccessPolicy {
     Never,
     Notify,
     Always,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum WebBrowserAccess {
     Ignore,
     Notify,
     OpenInBrowser,
 }
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExternalInterfaceMode {
+    Disabled,
+    Log,
+    Actions,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AudioPrecision {
+    Int16,
+    Float,
+}
+
+/// How the mixer's output is interpolated when it has to be resampled to the output rate.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ResamplerQuality {
+    Nearest,
+    Linear,
+    /// Windowed sinc; the most accurate, and the most expensive
+    Sinc,
+}
+
+/// libretro always takes interleaved stereo, so mono is sent as the same sample in both channels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AudioChannels {
+    Stereo,
+    Mono,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RightClickMode {
+    ContextMenu,
+    Input,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FallbackBackend {
+    Auto,
+    Gl,
+    Vulkan,
+}
+
+impl FallbackBackend {
+    pub fn backends(&self) -> wgpu::Backends {
+        match self {
+            FallbackBackend::Auto => wgpu::Backends::all(),
+            FallbackBackend::Gl => wgpu::Backends::GL,
+            FallbackBackend::Vulkan => wgpu::Backends::VULKAN,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AnalogCursor {
+    Off,
+    Linear,
+    Accelerated,
+}
+
+impl AnalogCursor {
+    /// Maps how far the stick is tilted (0 to 1, past the deadzone) to a fraction of the cursor's top speed.
+    pub fn response(&self, tilt: f32) -> f32 {
+        match self {
+            AnalogCursor::Off => 0.0,
+            AnalogCursor::Linear => tilt,
+            // Small tilts stay slow for precise aiming, full tilt still crosses the stage quickly
+            AnalogCursor::Accelerated => tilt * tilt,
+        }
+    }
+}
+
+/// Corrects relative mouse motion from frontends or devices that report it oddly.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MouseAxes {
+    Normal,
+    Swapped,
+    InvertX,
+    InvertY,
+    InvertBoth,
+}
+
+impl MouseAxes {
+    /// Maps the frontend's reported (x, y) motion to the motion the cursor should make.
+    pub fn apply(&self, dx: i16, dy: i16) -> (i16, i16) {
+        match self {
+            MouseAxes::Normal => (dx, dy),
+            MouseAxes::Swapped => (dy, dx),
+            MouseAxes::InvertX => (dx.saturating_neg(), dy),
+            MouseAxes::InvertY => (dx, dy.saturating_neg()),
+            MouseAxes::InvertBoth => (dx.saturating_neg(), dy.saturating_neg()),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AspectRatioMode {
+    Geometry,
+    Pad4x3,
+    Pad16x9,
+}
+
+impl AspectRatioMode {
+    /// The aspect ratio to pad the rendered texture to,
+    /// or `None` if the frontend is told the movie's own aspect ratio instead.
+    pub fn padded_aspect_ratio(&self) -> Option<f64> {
+        match self {
+            AspectRatioMode::Geometry => None,
+            AspectRatioMode::Pad4x3 => Some(4.0 / 3.0),
+            AspectRatioMode::Pad16x9 => Some(16.0 / 9.0),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EndBehavior {
+    /// Let the movie do whatever its timeline says
+    Loop,
+    /// Stop on the last frame
+    Freeze,
+    /// Go back to the first frame, even if the movie stopped itself
+    Restart,
+    /// Close the core
+    Shutdown,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyboardPassthrough {
+    /// Every key goes to the content
+    All,
+    /// Escape and F1, RetroArch's default quit and menu keys, are left to the frontend
+    ReserveMenu,
+    /// Escape and all function keys are left to the frontend
+    ReserveFunctionKeys,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LetterboxColor {
+    /// Leave the bars as Ruffle draws them
+    Black,
+    /// Match the movie's background color
+    Stage,
+    /// RGB
+    Custom([u8; 3]),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GuideOverlay {
+    Off,
+    /// Action-safe (90%) and title-safe (80%) rectangles
+    SafeArea,
+    /// Rule-of-thirds lines
+    Thirds,
+    /// A 10x10 grid
+    Grid,
+}
+
+impl GuideOverlay {
+    /// The overlay's number in `post_process.wgsl`.
+    pub fn shader_index(&self) -> u32 {
+        match self {
+            GuideOverlay::Off => 0,
+            GuideOverlay::SafeArea => 1,
+            GuideOverlay::Thirds => 2,
+            GuideOverlay::Grid => 3,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PostProcessPreset {
+    Off,
+    Scanlines,
+    Crt,
+}
+
+impl PostProcessPreset {
+    /// The preset's number in `post_process.wgsl`, or `None` if no pass is needed.
+    pub fn shader_index(&self) -> Option<u32> {
+        match self {
+            PostProcessPreset::Off => None,
+            PostProcessPreset::Scanlines => Some(1),
+            PostProcessPreset::Crt => Some(2),
+        }
+    }
+}
@@ -1,3 +1,9 @@
+pub mod fetch;
 pub mod keyboard;
+pub mod language;
 pub mod mouse;
-pub mod math;
\ No newline at end of file
+pub mod math;
+pub mod message;
+pub mod sidecar;
+pub mod swf;
+pub mod wav;
@@ -0,0 +1,93 @@
+use std::path::Path;
+use std::time::Duration;
+
+use isahc::config::{Configurable, RedirectPolicy};
+use isahc::{HttpClient, ReadResponseExt};
+use log::{debug, error, info};
+use url::Url;
+
+use crate::core::config::Config;
+
+/// Gives up on connecting to the server after this long.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Gives up on the whole download after this long, since the frontend is frozen until it finishes.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Returns the movie's URL if the content is a `.url` file, i.e. a text file naming an http(s) URL.
+/// Both a bare URL and the `[InternetShortcut]` format that Windows saves are accepted.
+pub fn remote_url(path: &Path, data: &[u8]) -> Option<Url> {
+    let is_url_file = path
+        .extension()
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("url"));
+    if !is_url_file {
+        return None;
+    }
+
+    std::str::from_utf8(data)
+        .ok()?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('[') && !line.starts_with(';'))
+        .find_map(|line| {
+            let line = line.strip_prefix("URL=").unwrap_or(line);
+            Url::parse(line).ok()
+        })
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Downloads the root movie, honoring the same HTTP settings the movie's own requests would use.
+/// Blocks until the download finishes, since there's nothing to play until then.
+pub fn fetch_movie(mut url: Url, config: &Config) -> Result<Vec<u8>, isahc::Error> {
+    if config.upgrade_to_https && url.scheme() == "http" {
+        if url.set_scheme("https").is_err() {
+            error!("Url::set_scheme failed on: {url}");
+        } else {
+            debug!("Upgraded {url} to HTTPS");
+        }
+    }
+
+    let mut builder = HttpClient::builder()
+        .redirect_policy(RedirectPolicy::Follow)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(DOWNLOAD_TIMEOUT);
+    for (name, value) in &config.http_headers {
+        builder = builder.default_header(name.as_str(), value.as_str());
+    }
+
+    info!("Downloading movie from {url}");
+    let mut response = builder.build()?.get(url.as_str())?;
+    if !response.status().is_success() {
+        return Err(isahc::Error::from(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("HTTP status is not ok, got {}", response.status()),
+        )));
+    }
+
+    Ok(response.bytes()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_bare_url() {
+        let url = remote_url(Path::new("game.url"), b"https://example.com/game.swf\n");
+        assert_eq!(url.as_ref().map(Url::as_str), Some("https://example.com/game.swf"));
+    }
+
+    #[test]
+    fn reads_internet_shortcut() {
+        let data = b"[InternetShortcut]\r\nURL=http://example.com/game.swf\r\n";
+        let url = remote_url(Path::new("game.URL"), data);
+        assert_eq!(url.as_ref().map(Url::as_str), Some("http://example.com/game.swf"));
+    }
+
+    #[test]
+    fn ignores_other_content() {
+        assert_eq!(remote_url(Path::new("game.swf"), b"https://example.com/game.swf"), None);
+        assert_eq!(remote_url(Path::new("game.url"), b"file:///etc/passwd"), None);
+        assert_eq!(remote_url(Path::new("game.url"), b"not a url"), None);
+    }
+}
@@ -0,0 +1,43 @@
+use ruffle_core::backend::ui::LanguageIdentifier;
+use rust_libretro::sys::retro_language;
+
+/// Locale used when the frontend's language is unknown or has no Flash equivalent.
+pub const DEFAULT_LOCALE: &str = "en-US";
+
+/// Maps the frontend's language to the locale that Flash reports via `Capabilities.language`.
+pub fn to_locale(language: retro_language) -> &'static str {
+    use rust_libretro::sys::retro_language::*;
+
+    match language {
+        RETRO_LANGUAGE_ENGLISH => "en-US",
+        RETRO_LANGUAGE_JAPANESE => "ja-JP",
+        RETRO_LANGUAGE_FRENCH => "fr-FR",
+        RETRO_LANGUAGE_SPANISH => "es-ES",
+        RETRO_LANGUAGE_GERMAN => "de-DE",
+        RETRO_LANGUAGE_ITALIAN => "it-IT",
+        RETRO_LANGUAGE_DUTCH => "nl-NL",
+        RETRO_LANGUAGE_PORTUGUESE_BRAZIL => "pt-BR",
+        RETRO_LANGUAGE_PORTUGUESE_PORTUGAL => "pt-PT",
+        RETRO_LANGUAGE_RUSSIAN => "ru-RU",
+        RETRO_LANGUAGE_KOREAN => "ko-KR",
+        RETRO_LANGUAGE_CHINESE_TRADITIONAL => "zh-TW",
+        RETRO_LANGUAGE_CHINESE_SIMPLIFIED => "zh-CN",
+        RETRO_LANGUAGE_POLISH => "pl-PL",
+        RETRO_LANGUAGE_VIETNAMESE => "vi-VN",
+        RETRO_LANGUAGE_ARABIC => "ar-SA",
+        RETRO_LANGUAGE_GREEK => "el-GR",
+        RETRO_LANGUAGE_TURKISH => "tr-TR",
+        RETRO_LANGUAGE_SLOVAK => "sk-SK",
+        RETRO_LANGUAGE_PERSIAN => "fa-IR",
+        RETRO_LANGUAGE_HEBREW => "he-IL",
+        RETRO_LANGUAGE_FINNISH => "fi-FI",
+        // Flash has no locale for Esperanto or Asturian, among others
+        _ => DEFAULT_LOCALE,
+    }
+}
+
+pub fn to_language_identifier(language: retro_language) -> LanguageIdentifier {
+    to_locale(language)
+        .parse()
+        .unwrap_or_else(|_| DEFAULT_LOCALE.parse().expect("DEFAULT_LOCALE should be a valid locale"))
+}
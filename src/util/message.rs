@@ -0,0 +1,38 @@
+use rust_libretro::environment;
+use rust_libretro::sys::{retro_environment_t, retro_log_level, retro_message_target, retro_message_type};
+use rust_libretro::types::MessageProgress;
+use rust_libretro::anyhow;
+
+/// Frames per second assumed when converting a duration for the basic message API.
+const MESSAGE_FRAME_RATE: u32 = 60;
+
+/// Shows a notification with RETRO_ENVIRONMENT_SET_MESSAGE_EXT if the frontend supports it,
+/// or falls back to the basic RETRO_ENVIRONMENT_SET_MESSAGE (which ignores priority and level) if not.
+pub unsafe fn set_message(
+    environ_cb: retro_environment_t,
+    message: &str,
+    duration: u32,
+    priority: u32,
+    level: retro_log_level,
+) -> anyhow::Result<()> {
+    match environment::get_message_interface_version(environ_cb) {
+        Ok(version) if version >= 1 => {
+            environment::set_message_ext(
+                environ_cb,
+                message,
+                duration,
+                priority,
+                level,
+                retro_message_target::RETRO_MESSAGE_TARGET_ALL,
+                retro_message_type::RETRO_MESSAGE_TYPE_NOTIFICATION,
+                MessageProgress::Indeterminate,
+            )?;
+        }
+        _ => {
+            let frames = (duration * MESSAGE_FRAME_RATE) / 1000;
+            environment::set_message(environ_cb, message, frames)?;
+        }
+    };
+
+    Ok(())
+}
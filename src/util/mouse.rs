@@ -4,19 +4,53 @@ use rust_libretro::contexts::RunContext;
 use rust_libretro_sys::*;
 
 use super::math::Pixels;
+use crate::options::{AnalogCursor, MouseAxes};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+/// How far the stick can be tilted before the cursor starts moving, as a fraction of its range.
+const ANALOG_DEADZONE: f32 = 0.15;
+
+/// How fast the stick moves the cursor at full tilt, in pixels per frame.
+const ANALOG_MAX_SPEED: f32 = 12.0;
+
+/// How far a touch can move, in pixels, before it counts as a drag instead of a tap.
+const DRAG_THRESHOLD: f32 = 8.0;
+
+/// The range of each `RETRO_DEVICE_POINTER` axis, which spans the whole viewport.
+const POINTER_RANGE: i32 = 0x7fff;
+
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub struct MouseState {
     pub position: Point2D<i16, Pixels>,
     pub delta: Vector2D<i16, Pixels>,
+    /// Movement that hasn't yet added up to a whole pixel.
+    pub remainder: Vector2D<f32, Pixels>,
     pub button: Option<MouseButton>,
     pub wheel: Option<MouseWheelDelta>,
+    /// Where the current touch started, if a pointer device is being pressed.
+    pub touch_origin: Option<Point2D<i16, Pixels>>,
+    /// Whether the current touch has moved far enough to count as a drag.
+    pub dragging: bool,
 }
 
 impl MouseState {
-    pub fn from_context(&self, geometry: &retro_game_geometry, ctx: &RunContext) -> Self {
-        let mouse_dx = ctx.get_input_state(0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_X);
-        let mouse_dy = ctx.get_input_state(0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_Y);
+    /// Reads the mouse's new state. If `confine` is set the cursor stays on the stage,
+    /// otherwise it may wander up to one stage-length past any edge.
+    /// The left analog stick also moves the cursor, following the `analog` response curve.
+    /// Raw movement is scaled by `sensitivity`, carrying fractional pixels over to later frames,
+    /// after `axes` corrects its direction.
+    pub fn from_context(
+        &self,
+        geometry: &retro_game_geometry,
+        ctx: &RunContext,
+        confine: bool,
+        sensitivity: f32,
+        analog: AnalogCursor,
+        axes: MouseAxes,
+    ) -> Self {
+        let (mouse_dx, mouse_dy) = axes.apply(
+            ctx.get_input_state(0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_X),
+            ctx.get_input_state(0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_Y),
+        );
         let mouse_left_button = ctx.get_input_state(0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_LEFT) != 0;
         let mouse_right_button = ctx.get_input_state(0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_RIGHT) != 0;
         let mouse_middle_button = ctx.get_input_state(0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_MIDDLE) != 0;
@@ -24,11 +58,26 @@ impl MouseState {
         let mouse_wheel_up = ctx.get_input_state(0, RETRO_DEVICE_MOUSE, 0, RETRO_DEVICE_ID_MOUSE_WHEELUP) != 0;
 
         let screen_size = Point2D::<i16, Pixels>::new(geometry.base_width as i16, geometry.base_height as i16);
-        let delta = vec2(mouse_dx, mouse_dy);
-        let new_position = (self.position + delta).clamp(Point2D::zero(), screen_size);
+        let movement = vec2::<f32, Pixels>(mouse_dx.into(), mouse_dy.into());
+        let movement = (movement + Self::analog_movement(ctx, analog)) * sensitivity + self.remainder;
+        let whole = movement.floor();
+        let delta = vec2(whole.x as i16, whole.y as i16);
+        let new_position = if confine {
+            (self.position + delta).clamp(Point2D::zero(), screen_size)
+        } else {
+            let min = Point2D::new(-screen_size.x, -screen_size.y);
+            let max = Point2D::new(screen_size.x.saturating_mul(2), screen_size.y.saturating_mul(2));
+            (self.position + delta).clamp(min, max)
+        };
 
         Self {
             delta,
+            // Don't let movement into an edge build up and "stick" when reversing
+            remainder: if new_position == self.position + delta {
+                movement - whole
+            } else {
+                Vector2D::zero()
+            },
             position: new_position,
             button: if mouse_left_button {
                 Some(MouseButton::Left)
@@ -46,6 +95,104 @@ impl MouseState {
             } else {
                 None
             },
+            touch_origin: None,
+            dragging: false,
+        }
+    }
+
+    /// Reads a touchscreen or other pointer device, which acts as the left mouse button while pressed.
+    /// The cursor stays where the touch began until it moves past a small threshold,
+    /// so a shaky tap still clicks what it landed on; after that it follows the touch, to allow dragging.
+    pub fn from_pointer(&self, geometry: &retro_game_geometry, ctx: &RunContext) -> Self {
+        let pressed = ctx.get_input_state(0, RETRO_DEVICE_POINTER, 0, RETRO_DEVICE_ID_POINTER_PRESSED) != 0;
+        if !pressed {
+            return Self {
+                delta: Vector2D::zero(),
+                button: None,
+                touch_origin: None,
+                dragging: false,
+                ..*self
+            };
+        }
+
+        let x = ctx.get_input_state(0, RETRO_DEVICE_POINTER, 0, RETRO_DEVICE_ID_POINTER_X);
+        let y = ctx.get_input_state(0, RETRO_DEVICE_POINTER, 0, RETRO_DEVICE_ID_POINTER_Y);
+        let to_pixels = |axis: i16, size: u32| {
+            (i64::from(i32::from(axis) + POINTER_RANGE) * i64::from(size) / i64::from(POINTER_RANGE * 2)) as i16
+        };
+        let touch = Point2D::new(to_pixels(x, geometry.base_width), to_pixels(y, geometry.base_height));
+
+        let (origin, dragging) = match self.touch_origin {
+            Some(origin) => (origin, self.dragging || (touch - origin).to_f32().length() > DRAG_THRESHOLD),
+            // A new touch moves the cursor to where it landed
+            None => (touch, false),
+        };
+        let position = if dragging || self.touch_origin.is_none() {
+            touch
+        } else {
+            self.position
+        };
+
+        Self {
+            position,
+            delta: position - self.position,
+            remainder: Vector2D::zero(),
+            button: Some(MouseButton::Left),
+            wheel: None,
+            touch_origin: Some(origin),
+            dragging,
+        }
+    }
+
+    /// How far the left stick moves the cursor this frame.
+    fn analog_movement(ctx: &RunContext, analog: AnalogCursor) -> Vector2D<f32, Pixels> {
+        if analog == AnalogCursor::Off {
+            return Vector2D::zero();
         }
+
+        let x = ctx.get_input_state(0, RETRO_DEVICE_ANALOG, RETRO_DEVICE_INDEX_ANALOG_LEFT, RETRO_DEVICE_ID_ANALOG_X);
+        let y = ctx.get_input_state(0, RETRO_DEVICE_ANALOG, RETRO_DEVICE_INDEX_ANALOG_LEFT, RETRO_DEVICE_ID_ANALOG_Y);
+        let stick = vec2::<f32, Pixels>(x.into(), y.into()) / f32::from(i16::MAX);
+
+        // Radial, so diagonals don't get a larger deadzone than the axes
+        let magnitude = stick.length();
+        if magnitude <= ANALOG_DEADZONE {
+            return Vector2D::zero();
+        }
+
+        let tilt = ((magnitude - ANALOG_DEADZONE) / (1.0 - ANALOG_DEADZONE)).min(1.0);
+        stick / magnitude * analog.response(tilt) * ANALOG_MAX_SPEED
+    }
+
+    /// This state, but with the cursor left where it was in `previous`.
+    pub fn without_motion(self, previous: &MouseState) -> Self {
+        Self {
+            position: previous.position,
+            delta: Vector2D::zero(),
+            remainder: Vector2D::zero(),
+            ..self
+        }
+    }
+
+    /// The cursor's position, with nothing pressed and no movement pending.
+    pub fn at_rest(&self) -> Self {
+        Self {
+            position: self.position,
+            ..Self::default()
+        }
+    }
+
+    /// The wheel's movement this frame, reversed if `invert` is set.
+    pub fn wheel_delta(&self, invert: bool) -> Option<MouseWheelDelta> {
+        match (self.wheel, invert) {
+            (Some(MouseWheelDelta::Lines(lines)), true) => Some(MouseWheelDelta::Lines(-lines)),
+            (Some(MouseWheelDelta::Pixels(pixels)), true) => Some(MouseWheelDelta::Pixels(-pixels)),
+            (wheel, _) => wheel,
+        }
+    }
+
+    pub fn is_on_stage(&self, geometry: &retro_game_geometry) -> bool {
+        (0..=geometry.base_width as i16).contains(&self.position.x)
+            && (0..=geometry.base_height as i16).contains(&self.position.y)
     }
 }
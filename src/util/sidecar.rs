@@ -0,0 +1,40 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{debug, warn};
+
+/// Extension of the sidecar file that holds extra HTTP headers for a movie.
+const HTTP_HEADERS_EXTENSION: &str = "headers";
+
+/// Returns the path of the sidecar file with the given extension that sits next to the movie,
+/// e.g. `game.swf` becomes `game.headers`.
+pub fn sidecar_path(movie_path: &Path, extension: &str) -> PathBuf {
+    movie_path.with_extension(extension)
+}
+
+/// Reads the HTTP headers that should be sent with every request the movie makes.
+/// Each non-empty line of the sidecar is a `Name: Value` pair; lines starting with `#` are ignored.
+/// A missing sidecar is not an error, it just means there are no extra headers.
+pub fn read_http_headers(movie_path: &Path) -> Vec<(String, String)> {
+    let path = sidecar_path(movie_path, HTTP_HEADERS_EXTENSION);
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            debug!("No HTTP headers loaded from {path:?}: {e}");
+            return Vec::new();
+        }
+    };
+
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| match line.split_once(':') {
+            Some((name, value)) if !name.trim().is_empty() => Some((name.trim().to_string(), value.trim().to_string())),
+            _ => {
+                warn!("Ignoring malformed header line in {path:?}: {line}");
+                None
+            }
+        })
+        .collect()
+}
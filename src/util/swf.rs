@@ -0,0 +1,156 @@
+use ruffle_core::swf::FileAttributes;
+use ruffle_core::tag_utils::SwfMovie;
+
+/// Tag code of the FileAttributes tag.
+const FILE_ATTRIBUTES_TAG: u16 = 69;
+
+/// Tag code of the DefineVideoStream tag.
+const DEFINE_VIDEO_STREAM_TAG: u16 = 60;
+
+/// Tag codes of the tags that hold ActionScript, directly or in a nested timeline or clip event:
+/// DoAction, PlaceObject2, DefineSprite, DoInitAction, PlaceObject3, DoABC, and DoABC2.
+const CODE_TAGS: [u16; 7] = [12, 26, 39, 59, 70, 72, 82];
+
+/// Tag codes of DefineButton and DefineButton2.
+const BUTTON_TAGS: [u16; 2] = [7, 34];
+
+/// Names that only show up in code that polls or listens to keys:
+/// AVM1's `Key.isDown` and `onKeyDown`, and the event type of AVM2's `KeyboardEvent.KEY_DOWN`.
+const KEYBOARD_NAMES: [&[u8]; 3] = [b"isDown", b"onKeyDown", b"keyDown"];
+
+/// Names that show up in code that handles clicks:
+/// AVM1's button handlers, and the event types of AVM2's `MouseEvent.CLICK` and `MouseEvent.MOUSE_DOWN`.
+const MOUSE_NAMES: [&[u8]; 4] = [b"onRelease", b"onPress", b"click", b"mouseDown"];
+
+/// Where the codec ID sits in a DefineVideoStream tag's body, after the ID, frame count, size, and flags.
+const VIDEO_CODEC_OFFSET: usize = 9;
+
+/// The newest SWF version whose features Ruffle covers reasonably well (Flash Player 11.2).
+/// Later versions mostly add Stage3D and ActionScript 3 APIs.
+pub const NEWEST_WELL_SUPPORTED_VERSION: u8 = 15;
+
+/// Reads the movie's FileAttributes tag, which SWF 8 and later require to be the first tag.
+/// Returns `None` for older movies that don't have one.
+pub fn file_attributes(movie: &SwfMovie) -> Option<FileAttributes> {
+    let data = movie.data();
+    let header = u16::from_le_bytes([*data.first()?, *data.get(1)?]);
+    let (code, length) = (header >> 6, header & 0x3f);
+    if code != FILE_ATTRIBUTES_TAG {
+        return None;
+    }
+
+    // FileAttributes is always 4 bytes long, so it never uses the long length form
+    let flags = if length == 0x3f { data.get(6)? } else { data.get(2)? };
+    Some(FileAttributes::from_bits_truncate(*flags))
+}
+
+/// The Flash Player release that introduced the given SWF version, for showing to users.
+pub fn flash_player_version(swf_version: u8) -> String {
+    match swf_version {
+        0..=10 => swf_version.to_string(),
+        11 => "10.2".to_string(),
+        12 => "10.3".to_string(),
+        13..=22 => format!("11.{}", swf_version - 13),
+        _ => (swf_version - 11).to_string(),
+    }
+}
+
+/// A video codec, by the ID a DefineVideoStream tag declares it with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct VideoCodec(pub u8);
+
+impl VideoCodec {
+    pub fn name(&self) -> &'static str {
+        match self.0 {
+            2 => "Sorenson H.263",
+            3 => "Screen Video",
+            4 => "On2 VP6",
+            5 => "On2 VP6 with alpha",
+            6 => "Screen Video V2",
+            7 => "H.264",
+            _ => "an unknown codec",
+        }
+    }
+
+    /// Whether `SoftwareVideoBackend` can decode this codec.
+    pub fn is_supported(&self) -> bool {
+        matches!(self.0, 2..=5)
+    }
+}
+
+/// The codecs of the movie's embedded video streams, each listed once.
+/// Videos the movie loads at runtime (e.g. FLV files through NetStream) can't be known ahead of time.
+pub fn video_codecs(movie: &SwfMovie) -> Vec<VideoCodec> {
+    let mut codecs = Vec::new();
+    for_each_tag(movie.data(), |code, body| {
+        if code == DEFINE_VIDEO_STREAM_TAG {
+            if let Some(&codec) = body.get(VIDEO_CODEC_OFFSET) {
+                if !codecs.contains(&VideoCodec(codec)) {
+                    codecs.push(VideoCodec(codec));
+                }
+            }
+        }
+    });
+
+    codecs
+}
+
+/// The input device a movie seems to be built around.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ControlHint {
+    Mouse,
+    Keyboard,
+}
+
+/// Guesses whether the movie is played with the keyboard or the mouse, going by the names its code uses.
+/// Any sign of keyboard handling wins, since games played with keys usually have clickable menus too.
+/// Returns `None` if the movie shows no sign of either, e.g. an animation.
+pub fn control_hint(movie: &SwfMovie) -> Option<ControlHint> {
+    let mut hint = None;
+    for_each_tag(movie.data(), |code, body| {
+        if hint == Some(ControlHint::Keyboard) {
+            return;
+        }
+
+        let uses_any = |names: &[&[u8]]| {
+            CODE_TAGS.contains(&code) && names.iter().any(|name| body.windows(name.len()).any(|window| window == *name))
+        };
+        if uses_any(&KEYBOARD_NAMES) {
+            hint = Some(ControlHint::Keyboard);
+        } else if BUTTON_TAGS.contains(&code) || uses_any(&MOUSE_NAMES) {
+            hint = Some(ControlHint::Mouse);
+        }
+    });
+
+    hint
+}
+
+/// Calls `f` with the code and body of each of the movie's top-level tags, in order.
+/// A truncated last tag gets whatever of its body is there.
+fn for_each_tag(data: &[u8], mut f: impl FnMut(u16, &[u8])) {
+    let mut offset = 0;
+    while let Some(header) = data.get(offset..offset + 2) {
+        let header = u16::from_le_bytes([header[0], header[1]]);
+        let (code, short_length) = (header >> 6, usize::from(header & 0x3f));
+        offset += 2;
+        let length = if short_length == 0x3f {
+            match data.get(offset..offset + 4) {
+                Some(length) => {
+                    offset += 4;
+                    u32::from_le_bytes([length[0], length[1], length[2], length[3]]) as usize
+                }
+                None => break,
+            }
+        } else {
+            short_length
+        };
+
+        let body = &data[offset.min(data.len())..];
+        f(code, &body[..length.min(body.len())]);
+
+        offset = match offset.checked_add(length) {
+            Some(offset) => offset,
+            None => break,
+        };
+    }
+}
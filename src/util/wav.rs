@@ -0,0 +1,82 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use log::warn;
+
+/// Size of the RIFF/WAVE header written by [`WavWriter`].
+const HEADER_SIZE: u32 = 44;
+
+/// The most sample data a WAV file can hold, since the 32-bit RIFF size also counts most of the header.
+const MAX_DATA_SIZE: u32 = u32::MAX - (HEADER_SIZE - 8);
+
+/// Writes interleaved 16-bit PCM to a WAV file.
+/// The header's sizes are filled in when the writer is finished or dropped.
+pub struct WavWriter {
+    file: BufWriter<File>,
+    data_size: u32,
+    /// Bytes per sample frame
+    block_align: u16,
+}
+
+impl WavWriter {
+    pub fn create(path: &Path, sample_rate: u32, channels: u16) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let block_align = channels * 2;
+
+        file.write_all(b"RIFF")?;
+        file.write_all(&0u32.to_le_bytes())?; // Filled in by finish()
+        file.write_all(b"WAVEfmt ")?;
+        file.write_all(&16u32.to_le_bytes())?;
+        file.write_all(&1u16.to_le_bytes())?; // PCM
+        file.write_all(&channels.to_le_bytes())?;
+        file.write_all(&sample_rate.to_le_bytes())?;
+        file.write_all(&(sample_rate * u32::from(block_align)).to_le_bytes())?;
+        file.write_all(&block_align.to_le_bytes())?;
+        file.write_all(&16u16.to_le_bytes())?; // Bits per sample
+        file.write_all(b"data")?;
+        file.write_all(&0u32.to_le_bytes())?; // Filled in by finish()
+
+        Ok(Self {
+            file,
+            data_size: 0,
+            block_align,
+        })
+    }
+
+    /// Writes as many whole sample frames as still fit; the rest are dropped once the file is full.
+    pub fn write_samples(&mut self, samples: &[i16]) -> io::Result<()> {
+        let block_align = usize::from(self.block_align);
+        let room = (MAX_DATA_SIZE - self.data_size) as usize / block_align * block_align / 2;
+        let samples = &samples[..samples.len().min(room)];
+        for sample in samples {
+            self.file.write_all(&sample.to_le_bytes())?;
+        }
+
+        self.data_size += (samples.len() * 2) as u32;
+        Ok(())
+    }
+
+    /// Whether the file has reached WAV's 4 GiB limit, after which a new one should be started.
+    pub fn is_full(&self) -> bool {
+        MAX_DATA_SIZE - self.data_size < u32::from(self.block_align)
+    }
+
+    /// Writes the final sizes into the header so the file can be played.
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(4))?;
+        self.file.write_all(&(HEADER_SIZE - 8).saturating_add(self.data_size).to_le_bytes())?;
+        self.file.seek(SeekFrom::Start(40))?;
+        self.file.write_all(&self.data_size.to_le_bytes())?;
+        self.file.seek(SeekFrom::End(0))?;
+        self.file.flush()
+    }
+}
+
+impl Drop for WavWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            warn!("Failed to finalize WAV file: {e}");
+        }
+    }
+}